@@ -0,0 +1,450 @@
+use accounting_ledger::model::error::Result;
+use accounting_ledger::model::{
+    add_transaction, close_year, export_journal, import_camt, import_csv, import_journal,
+    read_account_sheet, read_accounts, read_balance_sheet, read_control, read_income_statement,
+    read_journal, read_ledger, read_trial_balance, read_vat_return, reconcile, write_file,
+    write_ledger, AccountPrefixes, AccountType, AmountEntity, AmountSign, CsvMapping, FiscalYear,
+    ImportSummary, JournalFilter, TaxCode, TransactionEntity,
+};
+use chrono::{Local, NaiveDate};
+use clap::{Parser, Subcommand};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+#[derive(Parser)]
+#[command(version, about = "Accounting ledger CLI tool")]
+struct Cli {
+    /// Path to the ledger yaml file
+    #[arg(short, long, default_value = "data/ledger.yaml")]
+    file: PathBuf,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Checks all transactions and reports every problem found
+    Validate {
+        /// Calendar year all transactions have to be dated in
+        #[arg(long)]
+        fiscal_year: Option<i32>,
+    },
+    /// Prints the balance sheet at the given date
+    Balance {
+        /// Date of the balance sheet, defaults to today
+        #[arg(long)]
+        date: Option<NaiveDate>,
+    },
+    /// Prints the income statement for a period
+    Income {
+        /// First day of the period
+        #[arg(long)]
+        from: NaiveDate,
+        /// Last day of the period
+        #[arg(long)]
+        to: NaiveDate,
+    },
+    /// Prints the VAT return for a quarter
+    Vat {
+        #[arg(long)]
+        year: i32,
+        /// Quarter of the year, 1 to 4
+        #[arg(long, value_parser = clap::value_parser!(u32).range(1..=4))]
+        quarter: u32,
+    },
+    /// Lists the transactions by date
+    Journal {
+        /// First day of the listed transactions
+        #[arg(long)]
+        from: Option<NaiveDate>,
+        /// Last day of the listed transactions
+        #[arg(long)]
+        to: Option<NaiveDate>,
+        /// Only transactions booking onto this account, by name or number
+        #[arg(long)]
+        account: Option<String>,
+        /// Only transactions whose description contains this text
+        #[arg(long)]
+        description: Option<String>,
+    },
+    /// Lists all defined accounts
+    Accounts,
+    /// Prints the trial balance of all accounts
+    Trial,
+    /// Lists every movement on an account with its running balance
+    Sheet {
+        /// Name or number of the account
+        account: String,
+    },
+    /// Exports the ledger as ledger-cli/hledger journal
+    Export {
+        /// Journal file to write, printed if not given
+        #[arg(long)]
+        output: Option<PathBuf>,
+        /// Date of the opening entry, by default the start of the fiscal year
+        #[arg(long)]
+        opening_date: Option<NaiveDate>,
+    },
+    /// Closes the fiscal year and writes the ledger file of the next year
+    Close {
+        /// Last day of the fiscal year
+        #[arg(long)]
+        date: NaiveDate,
+        /// Equity account the profit or loss is transferred to
+        #[arg(long)]
+        equity: String,
+        /// Ledger file of the next year
+        #[arg(long)]
+        output: PathBuf,
+    },
+    /// Adds a transaction to the ledger file
+    Add {
+        #[arg(long)]
+        date: NaiveDate,
+        #[arg(long)]
+        description: String,
+        /// Account which is debited
+        #[arg(long)]
+        debit: String,
+        /// Account which is credited
+        #[arg(long)]
+        credit: String,
+        #[arg(long)]
+        amount: AmountEntity,
+        /// Currency of the amount, defaults to the base currency
+        #[arg(long)]
+        currency: Option<String>,
+        /// VAT code if the amount includes tax
+        #[arg(long, value_parser = parse_tax_code)]
+        tax: Option<TaxCode>,
+    },
+    /// Appends the rows of a bank CSV export to the ledger
+    ImportCsv {
+        /// CSV export of the bank
+        statement: PathBuf,
+        /// Bank account the rows are booked onto, by name or number
+        #[arg(long)]
+        account: String,
+        /// Account booked against the bank account until the rows are assigned
+        #[arg(long, default_value = "Unassigned")]
+        counter_account: String,
+        #[arg(long, default_value = ",", value_parser = parse_delimiter)]
+        delimiter: u8,
+        #[arg(long, default_value = "Date")]
+        date_column: String,
+        #[arg(long, default_value = "%Y-%m-%d")]
+        date_format: String,
+        #[arg(long, default_value = "Amount")]
+        amount_column: String,
+        /// Positive amounts are outflows of the bank account
+        #[arg(long)]
+        outflow_positive: bool,
+        #[arg(long, default_value = ".")]
+        decimal_separator: char,
+        #[arg(long, default_value = "Description")]
+        description_column: String,
+    },
+    /// Appends the entries of a camt.053 bank statement to the ledger
+    ImportCamt {
+        /// camt.053 XML statement
+        statement: PathBuf,
+        /// Bank account the entries are booked onto, by name or number
+        #[arg(long)]
+        account: String,
+        /// Account booked against the bank account until the entries are assigned
+        #[arg(long, default_value = "Unassigned")]
+        counter_account: String,
+    },
+    /// Converts a ledger-cli/hledger journal into a new ledger file
+    ImportJournal {
+        /// Journal file
+        journal: PathBuf,
+        /// Ledger file to write
+        #[arg(long)]
+        output: PathBuf,
+        /// Name of the ledger, by default the name of the journal file
+        #[arg(long)]
+        name: Option<String>,
+        /// Base currency, amounts without commodity are in it
+        #[arg(long, default_value = "CHF")]
+        currency: String,
+        /// Account group of the accounts below a prefix, e.g. Assets:Anlagen=fixed-assets
+        #[arg(long = "prefix", value_parser = parse_prefix)]
+        prefixes: Vec<(String, AccountType)>,
+    },
+    /// Compares a working capital account with a bank statement
+    Reconcile {
+        /// camt.053 statement (.xml) or yaml statement with balances and entries
+        statement: PathBuf,
+        /// Working capital account, by name or number
+        #[arg(long)]
+        account: String,
+        /// Marks the matched transactions as reconciled in the ledger file
+        #[arg(long)]
+        mark: bool,
+    },
+}
+
+fn print_import(summary: &ImportSummary, statement: &Path) {
+    println!(
+        "imported {} transaction(s) from {}",
+        summary.imported,
+        statement.display()
+    );
+    if summary.skipped > 0 {
+        println!("skipped {} entry(s) imported before", summary.skipped);
+    }
+    if !summary.unmatched.is_empty() {
+        println!("no rule matched {} entry(s):", summary.unmatched.len());
+        for entry in summary.unmatched.iter() {
+            println!(
+                "  {:<12}{:<40}{:>14}",
+                entry.date.to_string(),
+                entry.description,
+                entry.amount
+            );
+        }
+    }
+}
+
+fn parse_delimiter(delimiter: &str) -> std::result::Result<u8, String> {
+    match delimiter.as_bytes() {
+        [byte] => Ok(*byte),
+        _ => Err(format!("delimiter {delimiter} is not a single character")),
+    }
+}
+
+fn parse_prefix(prefix: &str) -> std::result::Result<(String, AccountType), String> {
+    let (prefix, group) = prefix.split_once('=').ok_or_else(|| {
+        format!("prefix {prefix} has no account group, e.g. Assets=working-capital")
+    })?;
+    Ok((prefix.to_string(), group.parse()?))
+}
+
+fn parse_tax_code(code: &str) -> std::result::Result<TaxCode, String> {
+    match code {
+        "standard" => Ok(TaxCode::Standard),
+        "reduced" => Ok(TaxCode::Reduced),
+        "accommodation" => Ok(TaxCode::Accommodation),
+        "exempt" => Ok(TaxCode::Exempt),
+        _ => Err(format!(
+            "unknown tax code {code}, expected standard, reduced, accommodation or exempt"
+        )),
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<()> {
+    let file = || File::open(&cli.file);
+    match cli.command {
+        Command::Validate { fiscal_year } => {
+            let fiscal_year = fiscal_year.map(FiscalYear::calendar_year);
+            read_control(file()?, fiscal_year.as_ref())?;
+            println!("{} is valid", cli.file.display());
+        }
+        Command::Balance { date } => {
+            let date = date.unwrap_or_else(|| Local::now().date_naive());
+            let balance_sheet = read_balance_sheet(file()?, date)?;
+            print!("{balance_sheet}");
+            balance_sheet.check_balanced()?;
+        }
+        Command::Income { from, to } => {
+            print!("{}", read_income_statement(file()?, from, to)?);
+        }
+        Command::Vat { year, quarter } => {
+            let first_month = (quarter - 1) * 3 + 1;
+            let from = NaiveDate::from_ymd_opt(year, first_month, 1).unwrap_or(NaiveDate::MIN);
+            let to = NaiveDate::from_ymd_opt(
+                year + i32::from(quarter == 4),
+                (first_month + 2) % 12 + 1,
+                1,
+            )
+            .and_then(|next| next.pred_opt())
+            .unwrap_or(NaiveDate::MAX);
+            print!("{}", read_vat_return(file()?, from, to)?);
+        }
+        Command::Journal {
+            from,
+            to,
+            account,
+            description,
+        } => {
+            let filter = JournalFilter {
+                from,
+                to,
+                account,
+                description,
+            };
+            print!("{}", read_journal(file()?, &filter)?);
+        }
+        Command::Sheet { account } => {
+            print!("{}", read_account_sheet(file()?, &account)?);
+        }
+        Command::Trial => {
+            print!("{}", read_trial_balance(file()?)?);
+        }
+        Command::Accounts => {
+            for account in read_accounts(file()?)? {
+                let number = account.number().map(|n| n.to_string()).unwrap_or_default();
+                println!(
+                    "{number:<8}{:<30}{:<20}{:>16}",
+                    account.name(),
+                    account.account_type(),
+                    account.start()
+                );
+            }
+        }
+        Command::Export {
+            output,
+            opening_date,
+        } => {
+            let journal = export_journal(file()?, opening_date)?;
+            match output {
+                Some(output) => {
+                    write_file(&output, &journal.to_string())?;
+                    println!("{} written", output.display());
+                }
+                None => print!("{journal}"),
+            }
+        }
+        Command::Close {
+            date,
+            equity,
+            output,
+        } => {
+            let next_year = close_year(file()?, date, &equity)?;
+            write_ledger(&next_year, &output)?;
+            println!("{} written", output.display());
+        }
+        Command::Add {
+            date,
+            description,
+            debit,
+            credit,
+            amount,
+            currency,
+            tax,
+        } => {
+            let mut ledger = read_ledger(file()?)?;
+            let transaction = TransactionEntity::new(description, amount, debit, credit, date);
+            let transaction = match currency {
+                Some(currency) => transaction.with_currency(currency),
+                None => transaction,
+            };
+            let transaction = match tax {
+                Some(tax) => transaction.with_tax(tax),
+                None => transaction,
+            };
+            add_transaction(&mut ledger, transaction)?;
+            write_ledger(&ledger, &cli.file)?;
+        }
+        Command::ImportCsv {
+            statement,
+            account,
+            counter_account,
+            delimiter,
+            date_column,
+            date_format,
+            amount_column,
+            outflow_positive,
+            decimal_separator,
+            description_column,
+        } => {
+            let mapping = CsvMapping {
+                delimiter,
+                date_column,
+                date_format,
+                amount_column,
+                amount_sign: if outflow_positive {
+                    AmountSign::OutflowPositive
+                } else {
+                    AmountSign::InflowPositive
+                },
+                decimal_separator,
+                description_column,
+            };
+            let mut ledger = read_ledger(file()?)?;
+            let summary = import_csv(
+                &mut ledger,
+                File::open(&statement)?,
+                &mapping,
+                &account,
+                &counter_account,
+            )?;
+            write_ledger(&ledger, &cli.file)?;
+            print_import(&summary, &statement);
+        }
+        Command::Reconcile {
+            statement,
+            account,
+            mark,
+        } => {
+            let mut ledger = read_ledger(file()?)?;
+            let reconciliation = reconcile(
+                &mut ledger,
+                &account,
+                File::open(&statement)?,
+                &statement,
+                mark,
+            )?;
+            print!("{reconciliation}");
+            if mark {
+                write_ledger(&ledger, &cli.file)?;
+            }
+        }
+        Command::ImportCamt {
+            statement,
+            account,
+            counter_account,
+        } => {
+            let mut ledger = read_ledger(file()?)?;
+            let summary = import_camt(
+                &mut ledger,
+                File::open(&statement)?,
+                &account,
+                &counter_account,
+            )?;
+            write_ledger(&ledger, &cli.file)?;
+            print_import(&summary, &statement);
+        }
+        Command::ImportJournal {
+            journal,
+            output,
+            name,
+            currency,
+            prefixes,
+        } => {
+            let name = name.unwrap_or_else(|| {
+                journal
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().to_string())
+                    .unwrap_or_default()
+            });
+            let prefixes = prefixes
+                .into_iter()
+                .fold(AccountPrefixes::default(), |prefixes, (prefix, group)| {
+                    prefixes.with(&prefix, group)
+                });
+            let ledger = import_journal(File::open(&journal)?, name, &prefixes, &currency)?;
+            write_ledger(&ledger, &output)?;
+            println!(
+                "{} transaction(s) of {} written to {}",
+                ledger.transactions.len(),
+                journal.display(),
+                output.display()
+            );
+        }
+    }
+    Ok(())
+}
@@ -1,22 +1,600 @@
 use crate::model::entity::TransactionAccountReader;
+use crate::model::error::{AccError, TransactionError, TransactionProblem};
+use chrono::{Datelike, Local, NaiveDate};
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
+use std::io::Write;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
 
 mod core;
 mod entity;
 pub mod error;
 
-pub fn read_control(file: File) {
-    let ledger: entity::Entity =
-        serde_yaml::from_reader(file).expect("could not deserialize entity");
-    let accounts = core::from_ledger_definition(&ledger.definition);
-    let transactions = ledger
+pub use self::core::{
+    AccountPrefixes, AccountSheet, AccountSheetLine, AccountType, BalanceGroup, BalanceLine,
+    BalanceSheet, CalculatedAccount, Currencies, FiscalYear, IncomeStatement, Journal,
+    JournalEntry, JournalFilter, LedgerAccount, PlainTextJournal, Reconciliation,
+    ReconciliationItem, TransactionResult, TrialBalance, TrialBalanceLine, VatLine, VatReturn,
+};
+pub use entity::AmountEntity;
+pub use entity::AmountSign;
+pub use entity::BankStatement;
+pub use entity::CsvMapping;
+pub use entity::Entity;
+pub use entity::ImportSummary;
+pub use entity::StatementBalance;
+pub use entity::StatementEntry;
+pub use entity::TaxCode;
+pub use entity::TransactionEntity;
+
+/// Checks the opening balances and the assignment rules, then validates all transactions and reports every problem found.
+/// Transaction dates are checked against the `fiscal_year` if one is given, otherwise against the
+/// fiscal year declared in the ledger.
+pub fn read_control(file: File, fiscal_year: Option<&FiscalYear>) -> error::Result<()> {
+    let ledger = read_ledger(file)?;
+    let accounts = core::from_ledger_definition(&ledger.definition)?;
+    let currencies = Currencies::from(ledger.definition.currency());
+    core::check_currencies(&accounts, &currencies)?;
+    core::check_opening_balances(&accounts, &currencies)?;
+    core::Rules::from(ledger.definition.rules(), &accounts)?;
+    let declared = read_fiscal_year(&ledger)?;
+    check_transactions(
+        &read_booked_transactions(&ledger, &accounts)?,
+        &accounts,
+        &currencies,
+        fiscal_year.or(declared.as_ref()),
+    )
+}
+
+/// Validates the transactions, [AccError::InvalidTransactions] lists every problem found.
+fn check_transactions(
+    transactions: &[Box<dyn TransactionAccountReader>],
+    accounts: &HashMap<String, LedgerAccount>,
+    currencies: &Currencies,
+    fiscal_year: Option<&FiscalYear>,
+) -> error::Result<()> {
+    let errors = core::validate_transactions(transactions, accounts, currencies, fiscal_year);
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(AccError::InvalidTransactions(errors))
+    }
+}
+
+pub fn read_balances(file: File) -> error::Result<TransactionResult> {
+    let ledger = read_ledger(file)?;
+    let accounts = core::from_ledger_definition(&ledger.definition)?;
+    let currencies = Currencies::from(ledger.definition.currency());
+    core::calculate_transactions(
+        &read_booked_transactions(&ledger, &accounts)?,
+        &accounts,
+        &currencies,
+    )
+}
+
+pub fn read_balance_sheet(file: File, date: NaiveDate) -> error::Result<BalanceSheet> {
+    let ledger = read_ledger(file)?;
+    let accounts = core::from_ledger_definition(&ledger.definition)?;
+    let currencies = Currencies::from(ledger.definition.currency());
+    let result = core::calculate_transactions_until(
+        &read_booked_transactions(&ledger, &accounts)?,
+        &accounts,
+        &currencies,
+        date,
+    )?;
+    BalanceSheet::from(&result, date)
+}
+
+pub fn read_income_statement(
+    file: File,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> error::Result<IncomeStatement> {
+    let ledger = read_ledger(file)?;
+    let accounts = core::from_ledger_definition(&ledger.definition)?;
+    let currencies = Currencies::from(ledger.definition.currency());
+    let result = core::calculate_transactions_between(
+        &read_booked_transactions(&ledger, &accounts)?,
+        &accounts,
+        &currencies,
+        from,
+        to,
+    )?;
+    IncomeStatement::from(&result, from, to)
+}
+
+/// Sums turnover and tax per tax code of the transactions dated from `from` to `to`.
+pub fn read_vat_return(file: File, from: NaiveDate, to: NaiveDate) -> error::Result<VatReturn> {
+    let ledger = read_ledger(file)?;
+    let accounts = core::from_ledger_definition(&ledger.definition)?;
+    let currencies = Currencies::from(ledger.definition.currency());
+    core::check_transactions(
+        &read_booked_transactions(&ledger, &accounts)?,
+        &accounts,
+        &currencies,
+    )?;
+    VatReturn::from(
+        &read_transactions(&ledger),
+        &accounts,
+        &currencies,
+        &core::VatRates::from(ledger.definition.vat()),
+        from,
+        to,
+    )
+}
+
+pub fn read_journal(file: File, filter: &JournalFilter) -> error::Result<Journal> {
+    let ledger = read_ledger(file)?;
+    let accounts = core::from_ledger_definition(&ledger.definition)?;
+    Journal::from(&read_transactions(&ledger), &accounts, filter)
+}
+
+/// Lists every movement on the account, referenced by name or number, with its running balance.
+pub fn read_account_sheet(file: File, account: &str) -> error::Result<AccountSheet> {
+    let ledger = read_ledger(file)?;
+    let accounts = core::from_ledger_definition(&ledger.definition)?;
+    let currencies = Currencies::from(ledger.definition.currency());
+    AccountSheet::from(
+        &read_booked_transactions(&ledger, &accounts)?,
+        &accounts,
+        &currencies,
+        account,
+    )
+}
+
+/// Lists opening balance, debit and credit totals and closing balance of every account.
+pub fn read_trial_balance(file: File) -> error::Result<TrialBalance> {
+    let ledger = read_ledger(file)?;
+    let accounts = core::from_ledger_definition(&ledger.definition)?;
+    let currencies = Currencies::from(ledger.definition.currency());
+    TrialBalance::from(
+        &read_booked_transactions(&ledger, &accounts)?,
+        &accounts,
+        &currencies,
+    )
+}
+
+pub fn read_accounts(file: File) -> error::Result<Vec<LedgerAccount>> {
+    let ledger = read_ledger(file)?;
+    let mut accounts: Vec<LedgerAccount> = core::from_ledger_definition(&ledger.definition)?
+        .into_values()
+        .collect();
+    accounts.sort_by(LedgerAccount::cmp_by_number);
+    Ok(accounts)
+}
+
+/// Validates the transactions like [read_control], then closes the fiscal year at `date` and returns the
+/// ledger of the next year.
+/// Balance accounts start with their closing balance, income accounts at zero and the profit or loss
+/// is transferred to `equity_account`. The years of the closed fiscal year in the ledger name move one year ahead.
+pub fn close_year(file: File, date: NaiveDate, equity_account: &str) -> error::Result<Entity> {
+    let ledger = read_ledger(file)?;
+    let accounts = core::from_ledger_definition(&ledger.definition)?;
+    let currencies = Currencies::from(ledger.definition.currency());
+    let transactions = read_booked_transactions(&ledger, &accounts)?;
+    check_transactions(
+        &transactions,
+        &accounts,
+        &currencies,
+        read_fiscal_year(&ledger)?.as_ref(),
+    )?;
+    let result = core::calculate_transactions_until(&transactions, &accounts, &currencies, date)?;
+    BalanceSheet::from(&result, date)?.check_balanced()?;
+    let starts = core::closing_balances(&result, equity_account)?;
+    let first_year = ledger
+        .definition
+        .fiscal_year()
+        .map_or(date.year(), |fiscal_year| fiscal_year.start.year());
+    let name = next_year_name(ledger.name(), first_year..=date.year());
+    ledger.carry_forward(name, &starts)
+}
+
+/// Appends the transaction to the ledger after checking that its accounts are defined.
+pub fn add_transaction(ledger: &mut Entity, transaction: TransactionEntity) -> error::Result<()> {
+    let accounts = core::from_ledger_definition(&ledger.definition)?;
+    let new_transaction: Box<dyn TransactionAccountReader> = Box::new(transaction.clone());
+    let currencies = Currencies::from(ledger.definition.currency());
+    core::check_new_transactions(
+        &core::split_tax(vec![new_transaction], &accounts, ledger.definition.vat())?,
+        &accounts,
+        &currencies,
+        read_fiscal_year(ledger)?.as_ref(),
+    )?;
+    ledger.transactions.push(transaction);
+    Ok(())
+}
+
+/// Appends the rows of a bank CSV export as transactions between the bank account and the counter-account
+/// assigned by the first matching rule of the ledger. Rows no rule matches are booked onto the placeholder
+/// `counter_account` and listed as unmatched. The transactions are checked like [add_transaction] before
+/// any is appended, except that the placeholder does not need to be defined.
+pub fn import_csv(
+    ledger: &mut Entity,
+    statement: File,
+    mapping: &CsvMapping,
+    bank_account: &str,
+    counter_account: &str,
+) -> error::Result<ImportSummary> {
+    let entries = entity::read_csv_statement(statement, mapping)?;
+    append_statement(ledger, &entries, bank_account, counter_account)
+}
+
+/// Appends the entries of a camt.053 statement like [import_csv].
+/// Entries whose bank reference is already found in the ledger are skipped.
+pub fn import_camt(
+    ledger: &mut Entity,
+    statement: File,
+    bank_account: &str,
+    counter_account: &str,
+) -> error::Result<ImportSummary> {
+    let statement = entity::read_camt_statement(statement)?;
+    append_statement(ledger, &statement.entries, bank_account, counter_account)
+}
+
+/// Writes the ledger as ledger-cli/hledger journal. The opening entry is dated `opening_date`,
+/// by default the start of the fiscal year or, without one, the date of the earliest transaction.
+pub fn export_journal(
+    file: File,
+    opening_date: Option<NaiveDate>,
+) -> error::Result<PlainTextJournal> {
+    let ledger = read_ledger(file)?;
+    let accounts = core::from_ledger_definition(&ledger.definition)?;
+    let currencies = Currencies::from(ledger.definition.currency());
+    let opening_date = match opening_date {
+        Some(date) => date,
+        None => read_fiscal_year(&ledger)?
+            .map(|fiscal_year| fiscal_year.start())
+            .or_else(|| ledger.transactions.iter().map(|t| t.read_date()).min())
+            .unwrap_or_else(|| Local::now().date_naive()),
+    };
+    PlainTextJournal::from(
+        &ledger,
+        &read_booked_transactions(&ledger, &accounts)?,
+        &accounts,
+        &currencies,
+        opening_date,
+    )
+}
+
+/// Converts a ledger-cli/hledger journal into a ledger named `name`, see [AccountPrefixes] for
+/// the account groups. Reports every entry which cannot be represented.
+pub fn import_journal(
+    journal: File,
+    name: String,
+    prefixes: &AccountPrefixes,
+    base_currency: &str,
+) -> error::Result<Entity> {
+    let journal = entity::read_journal_file(journal)?;
+    core::import_journal_file(name, &journal, prefixes, base_currency)
+}
+
+/// Compares the working capital account with a bank statement, either a camt.053 file (`.xml`)
+/// or a yaml file with `balances` and `entries`. With `mark` the matched transactions are marked as reconciled.
+pub fn reconcile(
+    ledger: &mut Entity,
+    account: &str,
+    statement: File,
+    statement_path: &Path,
+    mark: bool,
+) -> error::Result<Reconciliation> {
+    let statement: BankStatement = match statement_path.extension() {
+        Some(extension) if extension.eq_ignore_ascii_case("xml") => {
+            entity::read_camt_statement(statement)?
+        }
+        _ => serde_yaml::from_reader(statement)?,
+    };
+    let accounts = core::from_ledger_definition(&ledger.definition)?;
+    let currencies = Currencies::from(ledger.definition.currency());
+    let reconciliation = Reconciliation::from(
+        &read_booked_transactions(ledger, &accounts)?,
+        &accounts,
+        &currencies,
+        account,
+        &statement,
+    )?;
+    if mark {
+        for index in reconciliation
+            .matched()
+            .iter()
+            .filter_map(|item| item.index())
+        {
+            ledger.transactions[index].mark_reconciled();
+        }
+    }
+    Ok(reconciliation)
+}
+
+pub fn read_ledger(file: File) -> error::Result<Entity> {
+    Ok(serde_yaml::from_reader(file)?)
+}
+
+/// Writes the ledger as yaml to `path`, see [write_file].
+pub fn write_ledger(ledger: &Entity, path: &Path) -> error::Result<()> {
+    let yaml =
+        serde_yaml::to_string(ledger).map_err(|err| AccError::Serialization(err.to_string()))?;
+    write_file(path, &yaml)
+}
+
+/// Writes the contents to a temporary file next to `path` and renames it over `path` once it is
+/// complete, so an existing file is either replaced entirely or left unchanged.
+pub fn write_file(path: &Path, contents: &str) -> error::Result<()> {
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".tmp");
+    let temporary = PathBuf::from(temporary);
+    let written = File::create(&temporary)
+        .and_then(|mut file| {
+            file.write_all(contents.as_bytes())?;
+            file.sync_all()
+        })
+        .and_then(|_| std::fs::rename(&temporary, path));
+    if written.is_err() {
+        let _ = std::fs::remove_file(&temporary);
+    }
+    Ok(written?)
+}
+
+fn append_statement(
+    ledger: &mut Entity,
+    entries: &[StatementEntry],
+    bank_account: &str,
+    counter_account: &str,
+) -> error::Result<ImportSummary> {
+    let accounts = core::from_ledger_definition(&ledger.definition)?;
+    let currencies = Currencies::from(ledger.definition.currency());
+    let bank = core::find_account(&accounts, bank_account)
+        .ok_or_else(|| AccError::UnknownAccount(bank_account.to_string()))?;
+    let rules = core::Rules::from(ledger.definition.rules(), &accounts)?;
+    let mut references: HashSet<String> = ledger
         .transactions
-        .into_iter()
+        .iter()
+        .filter_map(|transaction| transaction.reference().map(str::to_string))
+        .collect();
+    let mut summary = ImportSummary::default();
+    let mut transactions = vec![];
+    for entry in entries.iter().filter(|entry| !entry.amount.is_zero()) {
+        if let Some(reference) = &entry.reference {
+            if !references.insert(reference.clone()) {
+                summary.skipped += 1;
+                continue;
+            }
+        }
+        let transaction = match rules.assign(entry) {
+            Some((account, Some(tax))) => entry.to_transaction(bank.name(), account).with_tax(tax),
+            Some((account, None)) => entry.to_transaction(bank.name(), account),
+            None => {
+                summary.unmatched.push(entry.clone());
+                entry.to_transaction(bank.name(), counter_account)
+            }
+        };
+        let currency = entry
+            .currency
+            .as_deref()
+            .filter(|currency| *currency != currencies.code(bank.currency()))
+            .or(bank.currency());
+        transactions.push(match currency {
+            Some(currency) => transaction.with_currency(currency.to_string()),
+            None => transaction,
+        });
+        summary.imported += 1;
+    }
+    let new_transactions = transactions
+        .iter()
+        .cloned()
         .map(|transaction| Box::new(transaction) as Box<dyn TransactionAccountReader>)
         .collect();
-    core::check_transactions(&transactions, &accounts);
+    match core::check_new_transactions(
+        &core::split_tax(new_transactions, &accounts, ledger.definition.vat())?,
+        &accounts,
+        &currencies,
+        read_fiscal_year(ledger)?.as_ref(),
+    ) {
+        // The placeholder is usually not defined, `validate` reports it until the entries are assigned.
+        Err(AccError::InvalidTransactions(errors)) => {
+            let placeholder = TransactionProblem::UnknownAccount(counter_account.to_string());
+            let errors: Vec<TransactionError> = errors
+                .into_iter()
+                .filter(|error| error.problem != placeholder)
+                .collect();
+            if !errors.is_empty() {
+                return Err(AccError::InvalidTransactions(errors));
+            }
+        }
+        result => result?,
+    }
+    ledger.transactions.extend(transactions);
+    Ok(summary)
+}
+
+/// The name with every year within `years` replaced by the year after it, e.g. `Buchhaltung 2022/2023`
+/// becomes `Buchhaltung 2023/2024`. Other numbers are kept.
+fn next_year_name(name: &str, years: RangeInclusive<i32>) -> String {
+    let mut next_name = String::with_capacity(name.len());
+    let mut rest = name;
+    while let Some(start) = rest.find(|c: char| c.is_ascii_digit()) {
+        let digits = &rest[start..];
+        let end = digits
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(digits.len());
+        next_name.push_str(&rest[..start]);
+        match digits[..end].parse::<i32>() {
+            Ok(year) if end == 4 && years.contains(&year) => {
+                next_name.push_str(&(year + 1).to_string())
+            }
+            _ => next_name.push_str(&digits[..end]),
+        }
+        rest = &digits[end..];
+    }
+    next_name.push_str(rest);
+    next_name
+}
+
+fn read_fiscal_year(ledger: &Entity) -> error::Result<Option<FiscalYear>> {
+    ledger
+        .definition
+        .fiscal_year()
+        .map(FiscalYear::from)
+        .transpose()
+}
+
+fn read_transactions(ledger: &Entity) -> Vec<Box<dyn TransactionAccountReader>> {
+    ledger
+        .transactions
+        .iter()
+        .cloned()
+        .map(|transaction| Box::new(transaction) as Box<dyn TransactionAccountReader>)
+        .collect()
 }
 
-pub fn read_ledger(file: File) -> entity::Entity {
-    serde_yaml::from_reader(file).expect("could not deserialize entity")
+/// Transactions as they are booked, with their tax split off.
+fn read_booked_transactions(
+    ledger: &Entity,
+    accounts: &HashMap<String, LedgerAccount>,
+) -> error::Result<Vec<Box<dyn TransactionAccountReader>>> {
+    core::split_tax(read_transactions(ledger), accounts, ledger.definition.vat())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{append_statement, close_year, next_year_name, write_file};
+    use crate::model::entity::{AmountEntity, Entity, StatementEntry};
+    use crate::model::error::{AccError, Result, TransactionProblem};
+    use chrono::NaiveDate;
+
+    const LEDGER: &str = r#"
+name: Buchhaltung 2022
+definition:
+  balance:
+    active:
+      working-capital:
+        - name: Bank
+          start: 1000
+      fixed-assets: []
+    passive:
+      equity:
+        - name: Eigenkapital
+          start: 1000
+      debt-capital: []
+  income:
+    revenue:
+      - name: Ertrag
+    expense: []
+  fiscal-year:
+    start: 2022-01-01
+    end: 2022-12-31
+transactions: []
+"#;
+
+    fn entry(year: i32, amount: i64) -> StatementEntry {
+        StatementEntry {
+            date: NaiveDate::from_ymd_opt(year, 3, 1).unwrap(),
+            amount: AmountEntity::new(amount, 0),
+            description: "Zahlung".to_string(),
+            ..StatementEntry::default()
+        }
+    }
+
+    #[test]
+    fn test_append_statement_onto_placeholder() -> Result<()> {
+        let mut ledger: Entity = serde_yaml::from_str(LEDGER)?;
+
+        let summary = append_statement(&mut ledger, &[entry(2022, 50)], "Bank", "Unassigned")?;
+
+        assert_eq!(summary.imported, 1);
+        assert_eq!(ledger.transactions.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_append_statement_rejects_invalid_transactions() -> Result<()> {
+        let mut ledger: Entity = serde_yaml::from_str(LEDGER)?;
+
+        let result = append_statement(
+            &mut ledger,
+            &[entry(2022, 50), entry(2023, 20)],
+            "Bank",
+            "Unassigned",
+        );
+
+        match result {
+            Err(AccError::InvalidTransactions(errors)) => {
+                assert_eq!(errors.len(), 1);
+                assert_eq!(errors[0].index, 1);
+                assert!(matches!(
+                    errors[0].problem,
+                    TransactionProblem::OutsideFiscalYear(_)
+                ));
+            }
+            _ => panic!("expected invalid transactions"),
+        }
+        assert!(ledger.transactions.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_close_year_rejects_invalid_transactions() -> Result<()> {
+        let directory = std::env::temp_dir().join(format!("close-{}", std::process::id()));
+        std::fs::create_dir_all(&directory)?;
+        let path = directory.join("ledger.yaml");
+        let invalid = "
+  - date: 2022-05-01
+    description: Zahlung
+    deb: Bank
+    cred: Unbekannt
+    amount: 10";
+        std::fs::write(
+            &path,
+            LEDGER.replace("transactions: []", &format!("transactions:{invalid}")),
+        )?;
+
+        let result = close_year(
+            std::fs::File::open(&path)?,
+            NaiveDate::from_ymd_opt(2022, 12, 31).unwrap(),
+            "Eigenkapital",
+        );
+
+        match result {
+            Err(AccError::InvalidTransactions(errors)) => assert_eq!(
+                errors[0].problem,
+                TransactionProblem::UnknownAccount("Unbekannt".to_string())
+            ),
+            other => panic!("expected invalid transactions, got {other:?}"),
+        }
+        std::fs::remove_dir_all(&directory)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_next_year_name() {
+        assert_eq!(
+            next_year_name("Buchhaltung 2022", 2022..=2022),
+            "Buchhaltung 2023"
+        );
+        assert_eq!(
+            next_year_name("Buchhaltung 2022/2023", 2022..=2023),
+            "Buchhaltung 2023/2024"
+        );
+        assert_eq!(
+            next_year_name("Filiale 2023, Jahr 2023", 2023..=2023),
+            "Filiale 2024, Jahr 2024"
+        );
+        assert_eq!(next_year_name("Konto 120230", 2023..=2023), "Konto 120230");
+        assert_eq!(next_year_name("Haushalt", 2022..=2022), "Haushalt");
+    }
+
+    #[test]
+    fn test_write_file_replaces_file() -> Result<()> {
+        let directory = std::env::temp_dir().join(format!("ledger-{}", std::process::id()));
+        std::fs::create_dir_all(&directory)?;
+        let path = directory.join("ledger.yaml");
+        std::fs::write(&path, "name: old")?;
+
+        write_file(&path, "name: new")?;
+
+        assert_eq!(std::fs::read_to_string(&path)?, "name: new");
+        assert!(!directory.join("ledger.yaml.tmp").exists());
+        assert!(write_file(&directory.join("missing").join("ledger.yaml"), "").is_err());
+        std::fs::remove_dir_all(&directory)?;
+        Ok(())
+    }
 }
@@ -1,5 +1,57 @@
 mod account;
+mod account_sheet;
+mod balance_sheet;
+mod closing;
+mod currency;
+mod fiscal_year;
+mod income_statement;
+mod journal;
+mod opening;
+mod plain_text;
+mod plain_text_import;
+mod reconciliation;
+mod rules;
+#[cfg(test)]
+mod test_support;
 mod transaction;
+mod trial_balance;
+mod vat;
 
+pub use account::find_account;
 pub use account::from_ledger_definition;
+pub use account::AccountType;
+pub use account::LedgerAccount;
+pub use account_sheet::AccountSheet;
+pub use account_sheet::AccountSheetLine;
+pub use balance_sheet::BalanceGroup;
+pub use balance_sheet::BalanceLine;
+pub use balance_sheet::BalanceSheet;
+pub use closing::closing_balances;
+pub use currency::check_currencies;
+pub use currency::Currencies;
+pub use fiscal_year::FiscalYear;
+pub use income_statement::IncomeStatement;
+pub use journal::Journal;
+pub use journal::JournalEntry;
+pub use journal::JournalFilter;
+pub use opening::check_opening_balances;
+pub use plain_text::PlainTextJournal;
+pub use plain_text_import::import_journal_file;
+pub use plain_text_import::AccountPrefixes;
+pub use reconciliation::Reconciliation;
+pub use reconciliation::ReconciliationItem;
+pub use rules::Rules;
+pub use transaction::calculate_transactions;
+pub use transaction::calculate_transactions_between;
+pub use transaction::calculate_transactions_until;
+pub use transaction::check_new_transactions;
 pub use transaction::check_transactions;
+pub use transaction::validate_transactions;
+pub use transaction::CalculatedAccount;
+pub use transaction::TransactionResult;
+pub use trial_balance::TrialBalance;
+pub use trial_balance::TrialBalanceLine;
+pub use vat::split_tax;
+pub use vat::VatLine;
+pub use vat::VatRates;
+pub use vat::VatReturn;
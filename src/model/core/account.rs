@@ -1,28 +1,32 @@
 use super::super::entity::AccountEntity;
 use super::super::entity::AmountEntity;
 use crate::model::entity::AccountsReader;
+use crate::model::error::{AccError, Result};
+use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
 
 #[derive(Clone, PartialEq, Debug)]
-enum BalanceType {
+pub enum BalanceType {
     Passive(PassiveType),
     Active(ActiveType),
 }
 
 #[derive(Clone, PartialEq, Debug)]
-enum PassiveType {
+pub enum PassiveType {
     Equity,
     DebtCapital,
 }
 
 #[derive(Clone, PartialEq, Debug)]
-enum ActiveType {
+pub enum ActiveType {
     WorkingCapital,
     FixedAssets,
 }
 
 #[derive(Clone, PartialEq, Debug)]
-enum IncomeType {
+pub enum IncomeType {
     Revenue,
     Expense,
 }
@@ -33,10 +37,65 @@ pub enum AccountType {
     Income(IncomeType),
 }
 
+impl AccountType {
+    /// Active and expense accounts grow on the debit side, passive and revenue accounts on the credit side.
+    pub fn increases_on_debit(&self) -> bool {
+        matches!(
+            self,
+            AccountType::Balance(BalanceType::Active(_)) | AccountType::Income(IncomeType::Expense)
+        )
+    }
+}
+
+impl Display for AccountType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let label = match self {
+            AccountType::Balance(BalanceType::Active(ActiveType::WorkingCapital)) => {
+                "Working Capital"
+            }
+            AccountType::Balance(BalanceType::Active(ActiveType::FixedAssets)) => "Fixed Assets",
+            AccountType::Balance(BalanceType::Passive(PassiveType::Equity)) => "Equity",
+            AccountType::Balance(BalanceType::Passive(PassiveType::DebtCapital)) => "Debt Capital",
+            AccountType::Income(IncomeType::Revenue) => "Revenue",
+            AccountType::Income(IncomeType::Expense) => "Expense",
+        };
+        f.pad(label)
+    }
+}
+
+/// Reads the group names of the ledger definition, e.g. `working-capital`.
+impl FromStr for AccountType {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "working-capital" => Ok(AccountType::Balance(BalanceType::Active(
+                ActiveType::WorkingCapital,
+            ))),
+            "fixed-assets" => Ok(AccountType::Balance(BalanceType::Active(
+                ActiveType::FixedAssets,
+            ))),
+            "equity" => Ok(AccountType::Balance(BalanceType::Passive(PassiveType::Equity))),
+            "debt-capital" => Ok(AccountType::Balance(BalanceType::Passive(
+                PassiveType::DebtCapital,
+            ))),
+            "revenue" => Ok(AccountType::Income(IncomeType::Revenue)),
+            "expense" => Ok(AccountType::Income(IncomeType::Expense)),
+            _ => Err(format!(
+                "{s} is no account group, expected working-capital, fixed-assets, equity, debt-capital, revenue or expense"
+            )),
+        }
+    }
+}
+
 #[derive(Clone, PartialEq, Debug)]
 pub struct LedgerAccount {
     name: String,
+    parent: Option<String>,
+    number: Option<u32>,
     start: AmountEntity,
+    start_value: Option<AmountEntity>,
+    currency: Option<String>,
     account_type: AccountType,
 }
 
@@ -44,25 +103,113 @@ impl LedgerAccount {
     fn from(account: &AccountEntity, account_type: &AccountType) -> Self {
         LedgerAccount {
             name: String::from(&account.name),
-            start: account.start.clone(),
+            parent: None,
+            number: account.number,
+            start: account.start,
+            start_value: account.start_value,
+            currency: account.currency.clone(),
             account_type: account_type.clone(),
         }
     }
 
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn number(&self) -> Option<u32> {
+        self.number
+    }
+
+    /// Full name of the parent account for sub-accounts.
+    pub fn parent(&self) -> Option<&str> {
+        self.parent.as_deref()
+    }
+
+    /// Name prefixed with the account number if there is one, e.g. "1000 Kasse".
+    pub fn label(&self) -> String {
+        match self.number {
+            Some(number) => format!("{number} {}", self.name),
+            None => self.name.clone(),
+        }
+    }
+
+    /// Orders accounts by number, accounts without a number follow sorted by name.
+    pub fn cmp_by_number(&self, other: &Self) -> Ordering {
+        (self.number.is_none(), self.number, &self.name).cmp(&(
+            other.number.is_none(),
+            other.number,
+            &other.name,
+        ))
+    }
+
+    /// Opening balance in the currency of the account.
+    pub fn start(&self) -> AmountEntity {
+        self.start
+    }
+
+    /// Opening balance in the base currency if it is given for a foreign currency account.
+    pub fn start_value(&self) -> Option<AmountEntity> {
+        self.start_value
+    }
+
+    /// Currency the account is kept in, `None` for the base currency.
+    pub fn currency(&self) -> Option<&str> {
+        self.currency.as_deref()
+    }
+
+    pub fn account_type(&self) -> &AccountType {
+        &self.account_type
+    }
+
+    #[cfg(test)]
+    pub fn new(name: &str, start: AmountEntity, account_type: AccountType) -> Self {
+        LedgerAccount {
+            name: name.to_string(),
+            parent: None,
+            number: None,
+            start,
+            start_value: None,
+            currency: None,
+            account_type,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn with_parent(mut self, parent: &str) -> Self {
+        self.parent = Some(parent.to_string());
+        self
+    }
+
+    #[cfg(test)]
+    pub fn with_number(mut self, number: u32) -> Self {
+        self.number = Some(number);
+        self
+    }
+
+    #[cfg(test)]
+    pub fn with_currency(mut self, currency: &str) -> Self {
+        self.currency = Some(currency.to_string());
+        self
+    }
+
     #[cfg(test)]
     pub fn dummy() -> Self {
         LedgerAccount {
             name: "Dummy".to_string(),
+            parent: None,
+            number: None,
             start: AmountEntity::new(10, 0),
+            start_value: None,
+            currency: None,
             account_type: AccountType::Income(IncomeType::Expense),
         }
     }
 }
 
+/// Collects the accounts of all groups, an account name may only be defined once across all groups.
 pub fn from_ledger_definition(
     ledger_definition: &dyn AccountsReader,
-) -> HashMap<String, LedgerAccount> {
-    ledger_definition.get_equities();
+) -> Result<HashMap<String, LedgerAccount>> {
     let equities = read_accounts(
         ledger_definition.get_equities(),
         AccountType::Balance(BalanceType::Passive(PassiveType::Equity)),
@@ -97,37 +244,99 @@ pub fn from_ledger_definition(
     ]
     .concat();
 
-    ledger
-        .into_iter()
-        .map(|acc| (acc.name.clone(), acc.clone()))
-        .collect()
+    let mut accounts: HashMap<String, LedgerAccount> = HashMap::new();
+    let mut numbers: HashMap<u32, String> = HashMap::new();
+    for account in ledger.into_iter() {
+        if let Some(existing) = accounts.get(&account.name) {
+            return Err(AccError::DuplicateAccount {
+                name: account.name,
+                first_group: existing.account_type.to_string(),
+                second_group: account.account_type.to_string(),
+            });
+        }
+        if let Some(number) = account.number {
+            if let Some(existing) = numbers.insert(number, account.name.clone()) {
+                return Err(AccError::DuplicateAccountNumber {
+                    number,
+                    first_account: existing,
+                    second_account: account.name,
+                });
+            }
+        }
+        accounts.insert(account.name.clone(), account);
+    }
+    Ok(accounts)
+}
+
+/// An account with sub-accounts only collects their balances and cannot be booked onto directly.
+pub fn has_sub_accounts(accounts: &HashMap<String, LedgerAccount>, name: &str) -> bool {
+    accounts
+        .values()
+        .any(|account| account.parent() == Some(name))
+}
+
+/// Looks up an account by its name or, if no account has this name, by its number.
+pub fn find_account<'a>(
+    accounts: &'a HashMap<String, LedgerAccount>,
+    reference: &str,
+) -> Option<&'a LedgerAccount> {
+    accounts.get(reference).or_else(|| {
+        let number: u32 = reference.trim().parse().ok()?;
+        accounts
+            .values()
+            .find(|account| account.number == Some(number))
+    })
 }
 
 fn read_accounts(
     raw_accounts: Vec<AccountEntity>,
     account_type: AccountType,
 ) -> Vec<LedgerAccount> {
-    raw_accounts
-        .iter()
-        .map(|acc| LedgerAccount::from(acc, &account_type))
-        .collect()
+    let mut accounts = vec![];
+    for acc in raw_accounts.iter() {
+        read_account(acc, &account_type, None, &mut accounts);
+    }
+    accounts
+}
+
+/// Flattens the account and its sub-accounts.
+/// Sub-accounts inherit the account type and, unless they define their own, the currency of their parent.
+fn read_account(
+    raw_account: &AccountEntity,
+    account_type: &AccountType,
+    parent: Option<&LedgerAccount>,
+    accounts: &mut Vec<LedgerAccount>,
+) {
+    let mut account = LedgerAccount::from(raw_account, account_type);
+    if let Some(parent) = parent {
+        account.name = format!("{}:{}", parent.name, raw_account.name);
+        account.parent = Some(parent.name.clone());
+        if account.currency.is_none() {
+            account.currency = parent.currency.clone();
+        }
+    }
+    accounts.push(account.clone());
+    for child in raw_account.accounts.iter() {
+        read_account(child, account_type, Some(&account), accounts);
+    }
 }
 
 #[cfg(test)]
 mod tests {
 
     use super::{
-        from_ledger_definition, AccountType, ActiveType, BalanceType, IncomeType, LedgerAccount,
-        PassiveType,
+        find_account, from_ledger_definition, has_sub_accounts, AccountType, ActiveType,
+        BalanceType, IncomeType, LedgerAccount, PassiveType,
     };
 
     use super::super::super::entity::MockLedgerEntity;
 
     use super::AccountEntity;
+    use crate::model::error::{AccError, Result};
     use std::collections::HashMap;
 
     #[test]
-    fn test_from_ledger_definition() -> Result<(), serde_yaml::Error> {
+    fn test_from_ledger_definition() -> Result<()> {
         let mut mock_ledger_definition = MockLedgerEntity::new();
         let kasse = AccountEntity::new("Kasse", 10);
         let maschine = AccountEntity::new("Maschinen", 1000);
@@ -156,7 +365,7 @@ mod tests {
             .expect_get_debt_capital()
             .return_const(vec![fremdkapital]);
 
-        let ledger_accounts = from_ledger_definition(&mock_ledger_definition);
+        let ledger_accounts = from_ledger_definition(&mock_ledger_definition)?;
         assert_eq!(ledger_accounts.len(), 6);
         verify_account(
             &ledger_accounts,
@@ -191,6 +400,146 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_duplicate_account_in_different_groups() {
+        let mut mock_ledger_definition = MockLedgerEntity::new();
+        mock_ledger_definition
+            .expect_get_equities()
+            .return_const(vec![]);
+        mock_ledger_definition
+            .expect_get_fixed_assets()
+            .return_const(vec![]);
+        mock_ledger_definition
+            .expect_get_working_capital()
+            .return_const(vec![AccountEntity::new("Kasse", 10)]);
+        mock_ledger_definition
+            .expect_get_revenue()
+            .return_const(vec![]);
+        mock_ledger_definition
+            .expect_get_expenses()
+            .return_const(vec![AccountEntity::new("Kasse", 0)]);
+        mock_ledger_definition
+            .expect_get_debt_capital()
+            .return_const(vec![]);
+
+        match from_ledger_definition(&mock_ledger_definition) {
+            Err(AccError::DuplicateAccount {
+                name,
+                first_group,
+                second_group,
+            }) => {
+                assert_eq!(name, "Kasse");
+                assert_eq!(first_group, "Working Capital");
+                assert_eq!(second_group, "Expense");
+            }
+            _ => panic!("expected duplicate account error"),
+        }
+    }
+
+    #[test]
+    fn test_sub_accounts() -> Result<()> {
+        let mut mock_ledger_definition = MockLedgerEntity::new();
+        mock_ledger_definition
+            .expect_get_equities()
+            .return_const(vec![]);
+        mock_ledger_definition
+            .expect_get_fixed_assets()
+            .return_const(vec![]);
+        mock_ledger_definition
+            .expect_get_working_capital()
+            .return_const(vec![AccountEntity::new("Bank", 0)
+                .with_currency("EUR")
+                .with_accounts(vec![
+                    AccountEntity::new("UBS", 10),
+                    AccountEntity::new("PostFinance", 20),
+                ])]);
+        mock_ledger_definition
+            .expect_get_revenue()
+            .return_const(vec![]);
+        mock_ledger_definition
+            .expect_get_expenses()
+            .return_const(vec![]);
+        mock_ledger_definition
+            .expect_get_debt_capital()
+            .return_const(vec![]);
+
+        let ledger_accounts = from_ledger_definition(&mock_ledger_definition)?;
+
+        assert_eq!(ledger_accounts.len(), 3);
+        verify_account(
+            &ledger_accounts,
+            "Bank:UBS",
+            AccountType::Balance(BalanceType::Active(ActiveType::WorkingCapital)),
+        );
+        assert_eq!(ledger_accounts["Bank:PostFinance"].parent(), Some("Bank"));
+        assert_eq!(ledger_accounts["Bank:UBS"].currency(), Some("EUR"));
+        assert!(has_sub_accounts(&ledger_accounts, "Bank"));
+        assert!(!has_sub_accounts(&ledger_accounts, "Bank:UBS"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_duplicate_account_number() {
+        let mut mock_ledger_definition = MockLedgerEntity::new();
+        mock_ledger_definition
+            .expect_get_equities()
+            .return_const(vec![AccountEntity::new("Eigenkapital", 0).with_number(1000)]);
+        mock_ledger_definition
+            .expect_get_fixed_assets()
+            .return_const(vec![]);
+        mock_ledger_definition
+            .expect_get_working_capital()
+            .return_const(vec![AccountEntity::new("Kasse", 0).with_number(1000)]);
+        mock_ledger_definition
+            .expect_get_revenue()
+            .return_const(vec![]);
+        mock_ledger_definition
+            .expect_get_expenses()
+            .return_const(vec![]);
+        mock_ledger_definition
+            .expect_get_debt_capital()
+            .return_const(vec![]);
+
+        assert!(matches!(
+            from_ledger_definition(&mock_ledger_definition),
+            Err(AccError::DuplicateAccountNumber { number: 1000, .. })
+        ));
+    }
+
+    #[test]
+    fn test_find_account_by_name_or_number() {
+        let kasse = LedgerAccount::dummy().with_number(1000);
+        let accounts = HashMap::from([("Dummy".to_string(), kasse.clone())]);
+        assert_eq!(find_account(&accounts, "Dummy"), Some(&kasse));
+        assert_eq!(find_account(&accounts, "1000"), Some(&kasse));
+        assert_eq!(find_account(&accounts, "2000"), None);
+    }
+
+    #[test]
+    fn test_cmp_by_number() {
+        let mut accounts = [
+            LedgerAccount::dummy(),
+            LedgerAccount::dummy().with_number(2800),
+            LedgerAccount::dummy().with_number(1000),
+        ];
+        accounts.sort_by(LedgerAccount::cmp_by_number);
+        let numbers: Vec<Option<u32>> = accounts.iter().map(LedgerAccount::number).collect();
+        assert_eq!(numbers, vec![Some(1000), Some(2800), None]);
+    }
+
+    #[test]
+    fn test_increases_on_debit() {
+        assert!(
+            AccountType::Balance(BalanceType::Active(ActiveType::WorkingCapital))
+                .increases_on_debit()
+        );
+        assert!(AccountType::Income(IncomeType::Expense).increases_on_debit());
+        assert!(
+            !AccountType::Balance(BalanceType::Passive(PassiveType::Equity)).increases_on_debit()
+        );
+        assert!(!AccountType::Income(IncomeType::Revenue).increases_on_debit());
+    }
+
     fn verify_account(
         ledger_accounts: &HashMap<String, LedgerAccount>,
         name: &str,
@@ -198,7 +547,7 @@ mod tests {
     ) {
         let account = ledger_accounts
             .get(name)
-            .expect(format!("cannot find account with name {name}").as_str());
+            .unwrap_or_else(|| panic!("cannot find account with name {name}"));
         assert_eq!(account.account_type, account_type);
     }
 }
@@ -0,0 +1,244 @@
+use super::account::{find_account, LedgerAccount};
+use super::currency::Currencies;
+use super::transaction::{book_transactions, Booking};
+use crate::model::entity::{AmountEntity, PostingSide, TransactionAccountReader};
+use crate::model::error::{AccError, Result};
+use chrono::NaiveDate;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+const REPORT_WIDTH: usize = 100;
+
+/// Movement on the account with the balance after it.
+#[derive(Clone, PartialEq, Debug)]
+pub struct AccountSheetLine {
+    date: NaiveDate,
+    description: String,
+    counter_account: Option<String>,
+    side: PostingSide,
+    amount: AmountEntity,
+    balance: AmountEntity,
+}
+
+impl AccountSheetLine {
+    pub fn date(&self) -> NaiveDate {
+        self.date
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// Account on the opposite side, `None` if the transaction books onto several accounts there.
+    pub fn counter_account(&self) -> Option<&str> {
+        self.counter_account.as_deref()
+    }
+
+    pub fn side(&self) -> PostingSide {
+        self.side
+    }
+
+    pub fn amount(&self) -> AmountEntity {
+        self.amount
+    }
+
+    /// Running balance after the movement.
+    pub fn balance(&self) -> AmountEntity {
+        self.balance
+    }
+}
+
+/// Account sheet (Kontoblatt) listing every movement on one account in base currency.
+#[derive(Clone, PartialEq, Debug)]
+pub struct AccountSheet {
+    account: String,
+    opening: AmountEntity,
+    lines: Vec<AccountSheetLine>,
+    closing: AmountEntity,
+}
+
+impl AccountSheet {
+    /// Books all transactions and keeps the movements on the account, referenced by name or number.
+    /// The sheet of a parent account lists the movements on all of its sub-accounts and starts with
+    /// their rolled up opening balance.
+    pub fn from(
+        transactions: &[Box<dyn TransactionAccountReader>],
+        accounts: &HashMap<String, LedgerAccount>,
+        currencies: &Currencies,
+        reference: &str,
+    ) -> Result<Self> {
+        let account = find_account(accounts, reference)
+            .ok_or_else(|| AccError::UnknownAccount(reference.to_string()))?;
+        let sub_account_prefix = format!("{}:", account.name());
+        let mut bookings: Vec<Booking> = vec![];
+        let result = book_transactions(
+            transactions,
+            accounts,
+            currencies,
+            |_| true,
+            |booking| {
+                if booking.account == account.name()
+                    || booking.account.starts_with(&sub_account_prefix)
+                {
+                    bookings.push(booking)
+                }
+            },
+        )?;
+        let calculated = result
+            .get_account(account.name())
+            .ok_or_else(|| AccError::UnknownAccount(reference.to_string()))?;
+        let increases_on_debit = account.account_type().increases_on_debit();
+        let opening = result.rolled_up(calculated, &|account| Ok(account.start_amount()))?;
+        let mut balance = opening;
+        let mut lines = vec![];
+        for booking in bookings {
+            balance = if (booking.side == PostingSide::Debit) == increases_on_debit {
+                balance.checked_add(booking.amount)?
+            } else {
+                balance.checked_sub(booking.amount)?
+            };
+            lines.push(AccountSheetLine {
+                date: booking.date,
+                description: booking.description,
+                counter_account: booking.counter_account,
+                side: booking.side,
+                amount: booking.amount,
+                balance,
+            });
+        }
+        Ok(AccountSheet {
+            account: account.label(),
+            opening,
+            lines,
+            closing: result.rolled_up(calculated, &|account| Ok(account.current_amount()))?,
+        })
+    }
+
+    pub fn account(&self) -> &str {
+        &self.account
+    }
+
+    pub fn opening(&self) -> AmountEntity {
+        self.opening
+    }
+
+    pub fn lines(&self) -> &[AccountSheetLine] {
+        &self.lines
+    }
+
+    pub fn closing(&self) -> AmountEntity {
+        self.closing
+    }
+}
+
+impl Display for AccountSheet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Account Sheet {}", self.account)?;
+        writeln!(f, "{}", "=".repeat(REPORT_WIDTH))?;
+        writeln!(
+            f,
+            "{:<12}{:<28}{:<16}{:>14}{:>14}{:>16}",
+            "Date", "Description", "Counter", "Debit", "Credit", "Balance"
+        )?;
+        writeln!(f, "{:<84}{:>16}", "Opening Balance", self.opening)?;
+        for line in self.lines.iter() {
+            let (debit, credit) = match line.side {
+                PostingSide::Debit => (line.amount.to_string(), String::new()),
+                PostingSide::Credit => (String::new(), line.amount.to_string()),
+            };
+            writeln!(
+                f,
+                "{:<12}{:<28}{:<16}{:>14}{:>14}{:>16}",
+                line.date.to_string(),
+                line.description,
+                line.counter_account.as_deref().unwrap_or("Split"),
+                debit,
+                credit,
+                line.balance
+            )?;
+        }
+        writeln!(f, "{}", "-".repeat(REPORT_WIDTH))?;
+        writeln!(f, "{:<84}{:>16}", "Closing Balance", self.closing)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AccountSheet;
+    use crate::model::core::account::{AccountType, ActiveType, BalanceType, LedgerAccount};
+    use crate::model::core::currency::Currencies;
+    use crate::model::core::test_support::{date, ledger_accounts, mock_transaction};
+    use crate::model::entity::{AmountEntity, PostingSide};
+    use crate::model::error::{AccError, Result};
+
+    #[test]
+    fn test_account_sheet_running_balance() -> Result<()> {
+        let transactions = vec![
+            mock_transaction("Aufwand", "Kasse", AmountEntity::new(30, 0), date(5, 1)),
+            mock_transaction("Kasse", "Ertrag", AmountEntity::new(80, 0), date(2, 1)),
+            mock_transaction("Aufwand", "Ertrag", AmountEntity::new(10, 0), date(3, 1)),
+        ];
+
+        let sheet = AccountSheet::from(
+            &transactions,
+            &ledger_accounts([]),
+            &Currencies::default(),
+            "1000",
+        )?;
+
+        assert_eq!(sheet.opening(), AmountEntity::new(100, 0));
+        assert_eq!(sheet.lines().len(), 2);
+        assert_eq!(sheet.lines()[0].counter_account(), Some("Ertrag"));
+        assert_eq!(sheet.lines()[0].side(), PostingSide::Debit);
+        assert_eq!(sheet.lines()[0].balance(), AmountEntity::new(180, 0));
+        assert_eq!(sheet.lines()[1].counter_account(), Some("Aufwand"));
+        assert_eq!(sheet.lines()[1].balance(), AmountEntity::new(150, 0));
+        assert_eq!(sheet.closing(), AmountEntity::new(150, 0));
+        assert!(sheet.to_string().contains("Closing Balance"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_account_sheet_of_parent_account() -> Result<()> {
+        let bank_type = AccountType::Balance(BalanceType::Active(ActiveType::WorkingCapital));
+        let accounts = ledger_accounts([
+            LedgerAccount::new("Bank", AmountEntity::zero(), bank_type.clone()),
+            LedgerAccount::new("Bank:UBS", AmountEntity::new(50, 0), bank_type.clone())
+                .with_parent("Bank"),
+            LedgerAccount::new("Bank:PostFinance", AmountEntity::new(20, 0), bank_type)
+                .with_parent("Bank"),
+        ]);
+        let transactions = vec![
+            mock_transaction("Bank:UBS", "Ertrag", AmountEntity::new(40, 0), date(2, 1)),
+            mock_transaction(
+                "Aufwand",
+                "Bank:PostFinance",
+                AmountEntity::new(15, 0),
+                date(3, 1),
+            ),
+            mock_transaction("Aufwand", "Kasse", AmountEntity::new(5, 0), date(4, 1)),
+        ];
+
+        let sheet = AccountSheet::from(&transactions, &accounts, &Currencies::default(), "Bank")?;
+
+        assert_eq!(sheet.opening(), AmountEntity::new(70, 0));
+        assert_eq!(sheet.lines().len(), 2);
+        assert_eq!(sheet.lines()[0].balance(), AmountEntity::new(110, 0));
+        assert_eq!(sheet.lines()[1].balance(), AmountEntity::new(95, 0));
+        assert_eq!(sheet.closing(), AmountEntity::new(95, 0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_account_sheet_of_unknown_account() {
+        assert!(matches!(
+            AccountSheet::from(
+                &[],
+                &ledger_accounts([]),
+                &Currencies::default(),
+                "Unbekannt"
+            ),
+            Err(AccError::UnknownAccount(_))
+        ));
+    }
+}
@@ -0,0 +1,350 @@
+use super::account::{AccountType, ActiveType, BalanceType, IncomeType, PassiveType};
+use super::transaction::{CalculatedAccount, TransactionResult};
+use crate::model::entity::AmountEntity;
+use crate::model::error::{AccError, Result};
+use chrono::NaiveDate;
+use std::fmt::{Display, Formatter};
+
+const REPORT_WIDTH: usize = 50;
+
+/// Account line of a balance sheet, foreign currency accounts also show their balance in their own currency.
+#[derive(Clone, PartialEq, Debug)]
+pub struct BalanceLine {
+    label: String,
+    amount: AmountEntity,
+    foreign: Option<(String, AmountEntity)>,
+}
+
+impl BalanceLine {
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Amount in base currency.
+    pub fn amount(&self) -> AmountEntity {
+        self.amount
+    }
+
+    /// Currency and amount of a foreign currency account.
+    pub fn foreign(&self) -> Option<(&str, AmountEntity)> {
+        self.foreign
+            .as_ref()
+            .map(|(currency, amount)| (currency.as_str(), *amount))
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct BalanceGroup {
+    name: String,
+    accounts: Vec<BalanceLine>,
+    total: AmountEntity,
+}
+
+impl BalanceGroup {
+    fn new(name: &str, accounts: Vec<BalanceLine>, total: AmountEntity) -> Self {
+        BalanceGroup {
+            name: name.to_string(),
+            accounts,
+            total,
+        }
+    }
+
+    /// Lists every account with the rolled up amount of its sub-accounts, sub-accounts are indented.
+    fn from_accounts(result: &TransactionResult, account_type: &AccountType) -> Result<Self> {
+        let tree = result.account_tree(account_type);
+        let amount = |account: &CalculatedAccount| {
+            result.rolled_up(account, &|account| Ok(account.current_amount()))
+        };
+        let total = AmountEntity::checked_sum(
+            tree.iter()
+                .filter(|(depth, _)| *depth == 0)
+                .map(|(_, account)| amount(account))
+                .collect::<Result<Vec<_>>>()?,
+        )?;
+        let mut lines = vec![];
+        for (depth, account) in tree {
+            let foreign = match account.currency() {
+                Some(currency) => Some((
+                    currency.to_string(),
+                    result.rolled_up(account, &|account| {
+                        Ok(account.foreign_amount().unwrap_or_default())
+                    })?,
+                )),
+                None => None,
+            };
+            lines.push(BalanceLine {
+                label: format!("{}{}", "  ".repeat(depth), account.label()),
+                amount: amount(account)?,
+                foreign,
+            });
+        }
+        Ok(BalanceGroup::new(&account_type.to_string(), lines, total))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn accounts(&self) -> &[BalanceLine] {
+        &self.accounts
+    }
+
+    pub fn total(&self) -> AmountEntity {
+        self.total
+    }
+}
+
+/// Two-sided balance sheet (Bilanz) at a given date.
+#[derive(Clone, PartialEq, Debug)]
+pub struct BalanceSheet {
+    date: NaiveDate,
+    active: Vec<BalanceGroup>,
+    passive: Vec<BalanceGroup>,
+    active_total: AmountEntity,
+    passive_total: AmountEntity,
+}
+
+impl BalanceSheet {
+    /// Builds the balance sheet from balances calculated up to `date`.
+    /// The not yet closed result of the income accounts is shown as its own group on the passive side.
+    pub fn from(result: &TransactionResult, date: NaiveDate) -> Result<Self> {
+        let revenue = AmountEntity::checked_sum(
+            result
+                .accounts()
+                .filter(|account| {
+                    account.account_type() == &AccountType::Income(IncomeType::Revenue)
+                })
+                .map(|account| account.current_amount()),
+        )?;
+        let expense = AmountEntity::checked_sum(
+            result
+                .accounts()
+                .filter(|account| {
+                    account.account_type() == &AccountType::Income(IncomeType::Expense)
+                })
+                .map(|account| account.current_amount()),
+        )?;
+        let profit = revenue.checked_sub(expense)?;
+        let active = vec![
+            BalanceGroup::from_accounts(
+                result,
+                &AccountType::Balance(BalanceType::Active(ActiveType::WorkingCapital)),
+            )?,
+            BalanceGroup::from_accounts(
+                result,
+                &AccountType::Balance(BalanceType::Active(ActiveType::FixedAssets)),
+            )?,
+        ];
+        let passive = vec![
+            BalanceGroup::from_accounts(
+                result,
+                &AccountType::Balance(BalanceType::Passive(PassiveType::DebtCapital)),
+            )?,
+            BalanceGroup::from_accounts(
+                result,
+                &AccountType::Balance(BalanceType::Passive(PassiveType::Equity)),
+            )?,
+            BalanceGroup::new(
+                "Profit/Loss",
+                vec![BalanceLine {
+                    label: "Profit/Loss".to_string(),
+                    amount: profit,
+                    foreign: None,
+                }],
+                profit,
+            ),
+        ];
+        Ok(BalanceSheet {
+            date,
+            active_total: AmountEntity::checked_sum(active.iter().map(BalanceGroup::total))?,
+            passive_total: AmountEntity::checked_sum(passive.iter().map(BalanceGroup::total))?,
+            active,
+            passive,
+        })
+    }
+
+    pub fn date(&self) -> NaiveDate {
+        self.date
+    }
+
+    pub fn active(&self) -> &[BalanceGroup] {
+        &self.active
+    }
+
+    pub fn passive(&self) -> &[BalanceGroup] {
+        &self.passive
+    }
+
+    pub fn active_total(&self) -> AmountEntity {
+        self.active_total
+    }
+
+    pub fn passive_total(&self) -> AmountEntity {
+        self.passive_total
+    }
+
+    pub fn difference(&self) -> Result<AmountEntity> {
+        self.active_total.checked_sub(self.passive_total)
+    }
+
+    pub fn is_balanced(&self) -> bool {
+        self.active_total == self.passive_total
+    }
+
+    pub fn check_balanced(&self) -> Result<()> {
+        if self.is_balanced() {
+            Ok(())
+        } else {
+            Err(AccError::UnbalancedLedger {
+                active: self.active_total(),
+                passive: self.passive_total(),
+            })
+        }
+    }
+}
+
+fn write_side(
+    f: &mut Formatter<'_>,
+    title: &str,
+    groups: &[BalanceGroup],
+    total: AmountEntity,
+) -> std::fmt::Result {
+    writeln!(f, "{title}")?;
+    writeln!(f, "{}", "=".repeat(REPORT_WIDTH))?;
+    for group in groups {
+        writeln!(f, "{}", group.name)?;
+        for line in group.accounts.iter() {
+            write!(f, "    {:<30}{:>16}", line.label, line.amount)?;
+            if let Some((currency, amount)) = line.foreign() {
+                write!(f, "  {currency} {amount}")?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(
+            f,
+            "  {:<32}{:>16}",
+            format!("Total {}", group.name),
+            group.total
+        )?;
+    }
+    writeln!(f, "{}", "-".repeat(REPORT_WIDTH))?;
+    writeln!(f, "{:<34}{:>16}", format!("Total {title}"), total)
+}
+
+impl Display for BalanceSheet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Balance Sheet per {}", self.date)?;
+        writeln!(f)?;
+        write_side(f, "Active", &self.active, self.active_total())?;
+        writeln!(f)?;
+        write_side(f, "Passive", &self.passive, self.passive_total())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BalanceSheet;
+    use crate::model::core::account::{
+        AccountType, ActiveType, BalanceType, IncomeType, LedgerAccount, PassiveType,
+    };
+    use crate::model::core::currency::Currencies;
+    use crate::model::core::transaction::calculate_transactions;
+    use crate::model::entity::AmountEntity;
+    use crate::model::error::Result;
+    use chrono::NaiveDate;
+    use std::collections::HashMap;
+
+    fn accounts(equity: i64) -> HashMap<String, LedgerAccount> {
+        [
+            LedgerAccount::new(
+                "Kasse",
+                AmountEntity::new(100, 0),
+                AccountType::Balance(BalanceType::Active(ActiveType::WorkingCapital)),
+            ),
+            LedgerAccount::new(
+                "Maschinen",
+                AmountEntity::new(50, 0),
+                AccountType::Balance(BalanceType::Active(ActiveType::FixedAssets)),
+            ),
+            LedgerAccount::new(
+                "Eigenkapital",
+                AmountEntity::new(equity, 0),
+                AccountType::Balance(BalanceType::Passive(PassiveType::Equity)),
+            ),
+            LedgerAccount::new(
+                "Ertrag",
+                AmountEntity::new(20, 0),
+                AccountType::Income(IncomeType::Revenue),
+            ),
+        ]
+        .into_iter()
+        .map(|account| (account.name().to_string(), account))
+        .collect()
+    }
+
+    #[test]
+    fn test_balanced_sheet() -> Result<()> {
+        let result = calculate_transactions(&[], &accounts(130), &Currencies::default())?;
+        let balance_sheet =
+            BalanceSheet::from(&result, NaiveDate::from_ymd_opt(2022, 12, 31).unwrap())?;
+
+        assert_eq!(balance_sheet.active_total(), AmountEntity::new(150, 0));
+        assert_eq!(balance_sheet.passive_total(), AmountEntity::new(150, 0));
+        assert_eq!(balance_sheet.active()[0].total(), AmountEntity::new(100, 0));
+        assert_eq!(balance_sheet.active()[1].total(), AmountEntity::new(50, 0));
+        assert_eq!(balance_sheet.passive()[2].total(), AmountEntity::new(20, 0));
+        assert!(balance_sheet.is_balanced());
+        assert!(balance_sheet.check_balanced().is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_accounts_sorted_by_number() -> Result<()> {
+        let mut accounts = accounts(130);
+        accounts.insert(
+            "Bank".to_string(),
+            LedgerAccount::new(
+                "Bank",
+                AmountEntity::zero(),
+                AccountType::Balance(BalanceType::Active(ActiveType::WorkingCapital)),
+            )
+            .with_number(1020),
+        );
+        accounts.insert(
+            "Post".to_string(),
+            LedgerAccount::new(
+                "Post",
+                AmountEntity::zero(),
+                AccountType::Balance(BalanceType::Active(ActiveType::WorkingCapital)),
+            )
+            .with_number(1010),
+        );
+        let result = calculate_transactions(&[], &accounts, &Currencies::default())?;
+        let balance_sheet =
+            BalanceSheet::from(&result, NaiveDate::from_ymd_opt(2022, 12, 31).unwrap())?;
+
+        let labels: Vec<&str> = balance_sheet.active()[0]
+            .accounts()
+            .iter()
+            .map(|line| line.label())
+            .collect();
+        assert_eq!(labels, vec!["1010 Post", "1020 Bank", "Kasse"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_unbalanced_sheet() -> Result<()> {
+        let result = calculate_transactions(&[], &accounts(100), &Currencies::default())?;
+        let balance_sheet =
+            BalanceSheet::from(&result, NaiveDate::from_ymd_opt(2022, 12, 31).unwrap())?;
+
+        assert_eq!(balance_sheet.difference()?, AmountEntity::new(30, 0));
+        assert!(!balance_sheet.is_balanced());
+        assert!(balance_sheet
+            .check_balanced()
+            .unwrap_err()
+            .to_string()
+            .contains("differ by 30.00"));
+        Ok(())
+    }
+}
@@ -0,0 +1,176 @@
+use super::account::{AccountType, BalanceType, IncomeType, PassiveType};
+use super::transaction::TransactionResult;
+use crate::model::entity::{AccountStart, AmountEntity};
+use crate::model::error::{AccError, Result};
+use std::collections::HashMap;
+
+/// Opening balances of the next fiscal year keyed by full account name.
+/// Balance accounts start with their closing balance in their own currency, income accounts start at zero
+/// and the profit or loss of the year is transferred to the equity account.
+/// Foreign currency accounts keep their closing value in the base currency, so the next year opens balanced.
+pub fn closing_balances(
+    result: &TransactionResult,
+    equity_account: &str,
+) -> Result<HashMap<String, AccountStart>> {
+    let equity = result
+        .get_account(equity_account)
+        .filter(|account| {
+            account.account_type()
+                == &AccountType::Balance(BalanceType::Passive(PassiveType::Equity))
+                && account.currency().is_none()
+                && result.sub_accounts(account.name()).is_empty()
+        })
+        .ok_or_else(|| AccError::InvalidEquityAccount(equity_account.to_string()))?;
+    let profit =
+        AmountEntity::checked_sum(
+            result
+                .accounts()
+                .map(|account| match account.account_type() {
+                    AccountType::Income(IncomeType::Revenue) => account.current_amount(),
+                    AccountType::Income(IncomeType::Expense) => -account.current_amount(),
+                    AccountType::Balance(_) => AmountEntity::zero(),
+                }),
+        )?;
+    result
+        .accounts()
+        .map(|account| {
+            let start = match account.account_type() {
+                AccountType::Income(_) => AccountStart::default(),
+                AccountType::Balance(_) if account.name() == equity.name() => AccountStart {
+                    amount: account.current_amount().checked_add(profit)?,
+                    value: None,
+                },
+                AccountType::Balance(_) => match account.foreign_amount() {
+                    Some(amount) => AccountStart {
+                        amount,
+                        value: Some(account.current_amount()),
+                    },
+                    None => AccountStart {
+                        amount: account.current_amount(),
+                        value: None,
+                    },
+                },
+            };
+            Ok((account.name().to_string(), start))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::closing_balances;
+    use crate::model::core::account::from_ledger_definition;
+    use crate::model::core::currency::{check_currencies, Currencies};
+    use crate::model::core::opening::check_opening_balances;
+    use crate::model::core::test_support::{date, ledger_accounts, mock_transaction};
+    use crate::model::core::transaction::calculate_transactions;
+    use crate::model::entity::{
+        AccountStart, AmountEntity, Entity, LedgerEntity, TransactionAccountReader,
+    };
+    use crate::model::error::{AccError, Result};
+
+    #[test]
+    fn test_closing_balances() -> Result<()> {
+        let transactions = vec![
+            mock_transaction("Kasse", "Ertrag", AmountEntity::new(80, 0), date(6, 1)),
+            mock_transaction("Aufwand", "Kasse", AmountEntity::new(30, 0), date(6, 1)),
+        ];
+        let result =
+            calculate_transactions(&transactions, &ledger_accounts([]), &Currencies::default())?;
+
+        let starts = closing_balances(&result, "2800")?;
+
+        assert_eq!(starts["Kasse"].amount, AmountEntity::new(150, 0));
+        assert_eq!(starts["Eigenkapital"].amount, AmountEntity::new(250, 0));
+        assert_eq!(starts["Ertrag"], AccountStart::default());
+        assert_eq!(starts["Aufwand"], AccountStart::default());
+        assert_eq!(starts["Kasse"].value, None);
+        Ok(())
+    }
+
+    const FOREIGN_LEDGER: &str = r#"
+name: Buchhaltung 2022
+definition:
+  balance:
+    active:
+      working-capital:
+        - name: Bank EUR
+          currency: EUR
+          start: 1000
+      fixed-assets: []
+    passive:
+      equity:
+        - name: Eigenkapital
+          start: 1000
+      debt-capital: []
+  income:
+    revenue:
+      - name: Ertrag
+      - name: Kursdifferenzen
+    expense: []
+  currency:
+    base: CHF
+    fx-account: Kursdifferenzen
+    rates:
+      - currency: EUR
+        date: 2022-01-01
+        rate: 1.0
+      - currency: EUR
+        date: 2022-06-01
+        rate: 0.95
+transactions:
+  - date: 2022-07-01
+    deb: Bank EUR
+    cred: Ertrag
+    amount: 100
+    currency: EUR
+    description: Verkauf
+"#;
+
+    #[test]
+    fn test_closing_foreign_account_opens_balanced() -> Result<()> {
+        let ledger: Entity = serde_yaml::from_str(FOREIGN_LEDGER)?;
+        let accounts = from_ledger_definition(&ledger.definition)?;
+        let currencies = Currencies::from(ledger.definition.currency());
+        let transactions: Vec<Box<dyn TransactionAccountReader>> = ledger
+            .transactions
+            .iter()
+            .cloned()
+            .map(|transaction| Box::new(transaction) as Box<dyn TransactionAccountReader>)
+            .collect();
+        let result = calculate_transactions(&transactions, &accounts, &currencies)?;
+
+        let starts = closing_balances(&result, "Eigenkapital")?;
+
+        assert_eq!(
+            starts["Bank EUR"],
+            AccountStart {
+                amount: AmountEntity::new(1100, 0),
+                value: Some(AmountEntity::new(1095, 0)),
+            }
+        );
+        let next_year = ledger.definition.carry_forward(&starts)?;
+        let yaml = serde_yaml::to_string(&next_year)?;
+        assert!(yaml.contains("start-value: 1095.0"));
+        let next_year: LedgerEntity = serde_yaml::from_str(&yaml)?;
+        let next_accounts = from_ledger_definition(&next_year)?;
+        check_currencies(&next_accounts, &currencies)?;
+        check_opening_balances(&next_accounts, &currencies)?;
+        let next_result = calculate_transactions(&[], &next_accounts, &currencies)?;
+        let bank = next_result.get_account("Bank EUR").unwrap();
+        assert_eq!(bank.current_amount(), AmountEntity::new(1095, 0));
+        assert_eq!(bank.foreign_amount(), Some(AmountEntity::new(1100, 0)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_closing_onto_non_equity_account() -> Result<()> {
+        let result = calculate_transactions(&[], &ledger_accounts([]), &Currencies::default())?;
+
+        assert!(matches!(
+            closing_balances(&result, "Kasse"),
+            Err(AccError::InvalidEquityAccount(_))
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,229 @@
+use super::account::{find_account, LedgerAccount};
+use crate::model::entity::{AmountEntity, CurrencyEntity, ExchangeRate};
+use crate::model::error::{AccError, Result};
+use chrono::NaiveDate;
+use std::collections::HashMap;
+
+/// Base currency of the ledger together with the exchange rates of the foreign currencies.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Currencies {
+    base: String,
+    fx_account: Option<String>,
+    /// Rates per currency, sorted by the date they are valid from.
+    rates: HashMap<String, Vec<(NaiveDate, ExchangeRate)>>,
+}
+
+impl Currencies {
+    pub fn from(definition: &CurrencyEntity) -> Self {
+        let mut rates: HashMap<String, Vec<(NaiveDate, ExchangeRate)>> = HashMap::new();
+        for rate in definition.rates.iter() {
+            rates
+                .entry(rate.currency.clone())
+                .or_default()
+                .push((rate.date, rate.rate));
+        }
+        for currency_rates in rates.values_mut() {
+            currency_rates.sort_by_key(|(date, _)| *date);
+        }
+        Currencies {
+            base: definition.base.clone(),
+            fx_account: definition.fx_account.clone(),
+            rates,
+        }
+    }
+
+    pub fn base(&self) -> &str {
+        &self.base
+    }
+
+    /// Account realized exchange rate gains and losses are booked onto.
+    pub fn fx_account(&self) -> Option<&str> {
+        self.fx_account.as_deref()
+    }
+
+    /// Currency code of an amount or account, `None` stands for the base currency.
+    pub fn code<'a>(&'a self, currency: Option<&'a str>) -> &'a str {
+        currency.unwrap_or(&self.base)
+    }
+
+    pub fn is_base(&self, currency: Option<&str>) -> bool {
+        self.code(currency) == self.base
+    }
+
+    /// Rate valid at `date`, which is the latest rate defined on or before that date.
+    pub fn rate(&self, currency: Option<&str>, date: NaiveDate) -> Option<ExchangeRate> {
+        if self.is_base(currency) {
+            return Some(ExchangeRate::one());
+        }
+        self.rates
+            .get(self.code(currency))?
+            .iter()
+            .rev()
+            .find(|(valid_from, _)| *valid_from <= date)
+            .map(|(_, rate)| *rate)
+    }
+
+    /// Earliest rate of the currency, opening balances of foreign currency accounts are converted with it.
+    pub fn opening_rate(&self, currency: Option<&str>) -> Option<ExchangeRate> {
+        if self.is_base(currency) {
+            return Some(ExchangeRate::one());
+        }
+        self.rates
+            .get(self.code(currency))?
+            .first()
+            .map(|(_, rate)| *rate)
+    }
+
+    /// Converts the amount into the base currency with the rate valid at `date`.
+    /// [AccError::MissingRate] if no rate of the currency is valid at that date.
+    pub fn to_base(
+        &self,
+        amount: AmountEntity,
+        currency: Option<&str>,
+        date: NaiveDate,
+    ) -> Result<AmountEntity> {
+        let rate = self
+            .rate(currency, date)
+            .ok_or_else(|| AccError::MissingRate(self.code(currency).to_string()))?;
+        amount.convert(rate)
+    }
+
+    /// Opening balance of the account in the base currency. Foreign currency accounts use their start value
+    /// if the ledger gives one and are converted with the opening rate otherwise.
+    pub fn opening_value(&self, account: &LedgerAccount) -> Result<AmountEntity> {
+        if let Some(value) = account
+            .start_value()
+            .filter(|_| !self.is_base(account.currency()))
+        {
+            return Ok(value);
+        }
+        let rate = self
+            .opening_rate(account.currency())
+            .ok_or_else(|| AccError::MissingRate(self.code(account.currency()).to_string()))?;
+        account.start().convert(rate)
+    }
+
+    /// Accounts in the base currency accept amounts in any currency, which are converted.
+    /// Foreign currency accounts only accept amounts in their own currency.
+    pub fn can_book(&self, account_currency: Option<&str>, amount_currency: Option<&str>) -> bool {
+        self.is_base(account_currency) || self.code(account_currency) == self.code(amount_currency)
+    }
+}
+
+impl Default for Currencies {
+    fn default() -> Self {
+        Currencies::from(&CurrencyEntity::default())
+    }
+}
+
+/// Every foreign currency used by an account needs a rate and realized exchange differences need an account.
+pub fn check_currencies(
+    accounts: &HashMap<String, LedgerAccount>,
+    currencies: &Currencies,
+) -> Result<()> {
+    let mut foreign_accounts = accounts
+        .values()
+        .filter(|account| !currencies.is_base(account.currency()))
+        .peekable();
+    if foreign_accounts.peek().is_none() {
+        return Ok(());
+    }
+    for account in foreign_accounts {
+        if currencies.opening_rate(account.currency()).is_none() {
+            return Err(AccError::MissingRate(
+                currencies.code(account.currency()).to_string(),
+            ));
+        }
+    }
+    match currencies.fx_account() {
+        None => Err(AccError::MissingFxAccount),
+        Some(fx_account) if find_account(accounts, fx_account).is_none() => {
+            Err(AccError::UnknownFxAccount(fx_account.to_string()))
+        }
+        Some(_) => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_currencies, Currencies};
+    use crate::model::core::account::{AccountType, ActiveType, BalanceType, LedgerAccount};
+    use crate::model::entity::{AmountEntity, CurrencyEntity, ExchangeRate, RateEntity};
+    use crate::model::error::AccError;
+    use chrono::NaiveDate;
+    use std::collections::HashMap;
+
+    fn currencies(fx_account: Option<&str>) -> Currencies {
+        let rate = |month: u32, rate: &str| RateEntity {
+            currency: "EUR".to_string(),
+            date: NaiveDate::from_ymd_opt(2022, month, 1).unwrap(),
+            rate: rate.parse().unwrap(),
+        };
+        Currencies::from(&CurrencyEntity {
+            base: "CHF".to_string(),
+            fx_account: fx_account.map(str::to_string),
+            rates: vec![rate(6, "0.95"), rate(1, "1.0")],
+        })
+    }
+
+    #[test]
+    fn test_rate_valid_at_date() {
+        let currencies = currencies(None);
+        let date = |month: u32, day: u32| NaiveDate::from_ymd_opt(2022, month, day).unwrap();
+
+        let rate = |micro_units: i64| Some(ExchangeRate::from_micro_units(micro_units));
+
+        assert_eq!(currencies.rate(Some("EUR"), date(5, 31)), rate(1_000_000));
+        assert_eq!(currencies.rate(Some("EUR"), date(6, 1)), rate(950_000));
+        assert_eq!(currencies.rate(Some("USD"), date(6, 1)), None);
+        assert_eq!(currencies.rate(None, date(6, 1)), rate(1_000_000));
+        assert_eq!(currencies.opening_rate(Some("EUR")), rate(1_000_000));
+        assert_eq!(
+            currencies
+                .to_base(AmountEntity::new(100, 0), Some("EUR"), date(7, 1))
+                .unwrap(),
+            AmountEntity::new(95, 0)
+        );
+        assert!(matches!(
+            currencies.to_base(AmountEntity::new(100, 0), Some("USD"), date(7, 1)),
+            Err(AccError::MissingRate(_))
+        ));
+    }
+
+    #[test]
+    fn test_can_book() {
+        let currencies = currencies(None);
+
+        assert!(currencies.can_book(None, Some("EUR")));
+        assert!(currencies.can_book(Some("EUR"), Some("EUR")));
+        assert!(!currencies.can_book(Some("EUR"), None));
+        assert!(!currencies.can_book(Some("EUR"), Some("USD")));
+    }
+
+    #[test]
+    fn test_check_currencies() {
+        let working_capital = AccountType::Balance(BalanceType::Active(ActiveType::WorkingCapital));
+        let accounts: HashMap<String, LedgerAccount> = [
+            LedgerAccount::new("Bank EUR", AmountEntity::zero(), working_capital.clone())
+                .with_currency("EUR"),
+            LedgerAccount::new("Kursdifferenzen", AmountEntity::zero(), working_capital),
+        ]
+        .into_iter()
+        .map(|account| (account.name().to_string(), account))
+        .collect();
+
+        assert!(check_currencies(&accounts, &currencies(Some("Kursdifferenzen"))).is_ok());
+        assert!(matches!(
+            check_currencies(&accounts, &currencies(None)),
+            Err(AccError::MissingFxAccount)
+        ));
+        assert!(matches!(
+            check_currencies(&accounts, &currencies(Some("Fehlt"))),
+            Err(AccError::UnknownFxAccount(_))
+        ));
+        assert!(matches!(
+            check_currencies(&accounts, &Currencies::default()),
+            Err(AccError::MissingRate(_))
+        ));
+    }
+}
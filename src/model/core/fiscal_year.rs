@@ -0,0 +1,184 @@
+use crate::model::entity::FiscalYearEntity;
+use crate::model::error::{AccError, Result};
+use chrono::{Months, NaiveDate};
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Period {
+    number: u32,
+    start: NaiveDate,
+    end: NaiveDate,
+    locked: bool,
+}
+
+impl Period {
+    /// Position within the fiscal year, the first period is 1.
+    pub fn number(&self) -> u32 {
+        self.number
+    }
+
+    pub fn start(&self) -> NaiveDate {
+        self.start
+    }
+
+    pub fn end(&self) -> NaiveDate {
+        self.end
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+
+    fn contains(&self, date: NaiveDate) -> bool {
+        self.start <= date && date <= self.end
+    }
+}
+
+/// Fiscal year divided into periods, transactions must be dated within the year and outside of locked periods.
+#[derive(Clone, PartialEq, Debug)]
+pub struct FiscalYear {
+    start: NaiveDate,
+    end: NaiveDate,
+    periods: Vec<Period>,
+}
+
+impl FiscalYear {
+    pub fn from(definition: &FiscalYearEntity) -> Result<Self> {
+        if definition.end < definition.start {
+            return Err(AccError::InvalidFiscalYear(format!(
+                "end {} is before start {}",
+                definition.end, definition.start
+            )));
+        }
+        let mut periods = vec![];
+        let mut start = definition.start;
+        while start <= definition.end {
+            let number = periods.len() as u32 + 1;
+            let next = if definition.monthly {
+                definition
+                    .start
+                    .checked_add_months(Months::new(number))
+                    .unwrap_or(NaiveDate::MAX)
+            } else {
+                NaiveDate::MAX
+            };
+            let end = next.pred_opt().unwrap_or(next).min(definition.end);
+            periods.push(Period {
+                number,
+                start,
+                end,
+                locked: definition.locked.contains(&number),
+            });
+            start = next;
+        }
+        if let Some(number) = definition
+            .locked
+            .iter()
+            .find(|number| **number == 0 || **number as usize > periods.len())
+        {
+            return Err(AccError::InvalidFiscalYear(format!(
+                "locked period {number} does not exist"
+            )));
+        }
+        Ok(FiscalYear {
+            start: definition.start,
+            end: definition.end,
+            periods,
+        })
+    }
+
+    /// Calendar year as a single open period.
+    pub fn calendar_year(year: i32) -> Self {
+        let start = NaiveDate::from_ymd_opt(year, 1, 1).unwrap_or(NaiveDate::MIN);
+        let end = NaiveDate::from_ymd_opt(year, 12, 31).unwrap_or(NaiveDate::MAX);
+        FiscalYear {
+            start,
+            end,
+            periods: vec![Period {
+                number: 1,
+                start,
+                end,
+                locked: false,
+            }],
+        }
+    }
+
+    pub fn start(&self) -> NaiveDate {
+        self.start
+    }
+
+    pub fn end(&self) -> NaiveDate {
+        self.end
+    }
+
+    pub fn periods(&self) -> &[Period] {
+        &self.periods
+    }
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.start <= date && date <= self.end
+    }
+
+    /// Period the date falls into, `None` outside of the fiscal year.
+    pub fn period(&self, date: NaiveDate) -> Option<&Period> {
+        self.periods.iter().find(|period| period.contains(date))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FiscalYear;
+    use crate::model::entity::FiscalYearEntity;
+    use crate::model::error::{AccError, Result};
+    use chrono::NaiveDate;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_monthly_periods() -> Result<()> {
+        let fiscal_year = FiscalYear::from(&FiscalYearEntity {
+            start: date(2022, 7, 1),
+            end: date(2023, 6, 30),
+            monthly: true,
+            locked: vec![2],
+        })?;
+
+        assert_eq!(fiscal_year.periods().len(), 12);
+        assert_eq!(fiscal_year.periods()[7].start(), date(2023, 2, 1));
+        assert_eq!(fiscal_year.periods()[7].end(), date(2023, 2, 28));
+        let august = fiscal_year.period(date(2022, 8, 31)).unwrap();
+        assert_eq!(august.number(), 2);
+        assert!(august.is_locked());
+        assert!(!fiscal_year.period(date(2022, 9, 1)).unwrap().is_locked());
+        assert!(fiscal_year.period(date(2023, 7, 1)).is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_single_period() -> Result<()> {
+        let fiscal_year = FiscalYear::from(&FiscalYearEntity {
+            start: date(2022, 1, 1),
+            end: date(2022, 12, 31),
+            monthly: false,
+            locked: vec![],
+        })?;
+
+        assert_eq!(fiscal_year, FiscalYear::calendar_year(2022));
+        assert!(fiscal_year.contains(date(2022, 12, 31)));
+        assert!(!fiscal_year.contains(date(2023, 1, 1)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_locked_period() {
+        let fiscal_year = FiscalYear::from(&FiscalYearEntity {
+            start: date(2022, 1, 1),
+            end: date(2022, 12, 31),
+            monthly: false,
+            locked: vec![2],
+        });
+
+        assert!(matches!(fiscal_year, Err(AccError::InvalidFiscalYear(_))));
+    }
+}
@@ -0,0 +1,178 @@
+use super::account::{AccountType, IncomeType};
+use super::transaction::{CalculatedAccount, TransactionResult};
+use crate::model::entity::AmountEntity;
+use crate::model::error::Result;
+use chrono::NaiveDate;
+use std::fmt::{Display, Formatter};
+
+const REPORT_WIDTH: usize = 50;
+
+/// Income statement (Erfolgsrechnung) listing the movement of every income account within a period.
+#[derive(Clone, PartialEq, Debug)]
+pub struct IncomeStatement {
+    from: NaiveDate,
+    to: NaiveDate,
+    revenue: Vec<(String, AmountEntity)>,
+    revenue_total: AmountEntity,
+    expense: Vec<(String, AmountEntity)>,
+    expense_total: AmountEntity,
+    profit: AmountEntity,
+}
+
+impl IncomeStatement {
+    /// Builds the income statement from balances calculated for the period `from` to `to`.
+    pub fn from(result: &TransactionResult, from: NaiveDate, to: NaiveDate) -> Result<Self> {
+        let (revenue, revenue_total) = period_movements(result, IncomeType::Revenue)?;
+        let (expense, expense_total) = period_movements(result, IncomeType::Expense)?;
+        Ok(IncomeStatement {
+            from,
+            to,
+            revenue,
+            revenue_total,
+            expense,
+            expense_total,
+            profit: revenue_total.checked_sub(expense_total)?,
+        })
+    }
+
+    pub fn from_date(&self) -> NaiveDate {
+        self.from
+    }
+
+    pub fn to_date(&self) -> NaiveDate {
+        self.to
+    }
+
+    pub fn revenue(&self) -> &[(String, AmountEntity)] {
+        &self.revenue
+    }
+
+    pub fn expense(&self) -> &[(String, AmountEntity)] {
+        &self.expense
+    }
+
+    pub fn revenue_total(&self) -> AmountEntity {
+        self.revenue_total
+    }
+
+    pub fn expense_total(&self) -> AmountEntity {
+        self.expense_total
+    }
+
+    /// Positive for a profit, negative for a loss.
+    pub fn profit(&self) -> AmountEntity {
+        self.profit
+    }
+}
+
+/// Lists every account with the rolled up movement of its sub-accounts, sub-accounts are indented.
+/// Returns the lines together with the total of the top level accounts.
+fn period_movements(
+    result: &TransactionResult,
+    income_type: IncomeType,
+) -> Result<(Vec<(String, AmountEntity)>, AmountEntity)> {
+    let tree = result.account_tree(&AccountType::Income(income_type));
+    let movement =
+        |account: &CalculatedAccount| result.rolled_up(account, &CalculatedAccount::movement);
+    let mut total = AmountEntity::zero();
+    let mut lines = vec![];
+    for (depth, account) in tree {
+        let amount = movement(account)?;
+        if depth == 0 {
+            total = total.checked_add(amount)?;
+        }
+        lines.push((format!("{}{}", "  ".repeat(depth), account.label()), amount));
+    }
+    Ok((lines, total))
+}
+
+fn write_group(
+    f: &mut Formatter<'_>,
+    title: &str,
+    accounts: &[(String, AmountEntity)],
+    total: AmountEntity,
+) -> std::fmt::Result {
+    writeln!(f, "{title}")?;
+    for (name, amount) in accounts.iter() {
+        writeln!(f, "    {name:<30}{amount:>16}")?;
+    }
+    writeln!(f, "  {:<32}{:>16}", format!("Total {title}"), total)
+}
+
+impl Display for IncomeStatement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Income Statement {} - {}", self.from, self.to)?;
+        writeln!(f, "{}", "=".repeat(REPORT_WIDTH))?;
+        write_group(f, "Revenue", &self.revenue, self.revenue_total())?;
+        write_group(f, "Expense", &self.expense, self.expense_total())?;
+        writeln!(f, "{}", "-".repeat(REPORT_WIDTH))?;
+        let profit = self.profit();
+        let label = if profit.is_negative() {
+            "Loss"
+        } else {
+            "Profit"
+        };
+        writeln!(f, "{label:<34}{:>16}", profit.abs())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IncomeStatement;
+    use crate::model::core::account::{AccountType, IncomeType, LedgerAccount};
+    use crate::model::core::currency::Currencies;
+    use crate::model::core::test_support::{date, ledger_accounts, mock_transaction};
+    use crate::model::core::transaction::calculate_transactions_between;
+    use crate::model::entity::{AmountEntity, TransactionAccountReader};
+    use crate::model::error::Result;
+
+    fn income_statement(
+        transactions: &[Box<dyn TransactionAccountReader>],
+    ) -> Result<IncomeStatement> {
+        let from = date(1, 1);
+        let to = date(6, 30);
+        let result = calculate_transactions_between(
+            transactions,
+            &ledger_accounts([LedgerAccount::new(
+                "Ertrag",
+                AmountEntity::new(5, 0),
+                AccountType::Income(IncomeType::Revenue),
+            )]),
+            &Currencies::default(),
+            from,
+            to,
+        )?;
+        IncomeStatement::from(&result, from, to)
+    }
+
+    #[test]
+    fn test_profit() -> Result<()> {
+        let transactions = vec![
+            mock_transaction("Kasse", "Ertrag", AmountEntity::new(80, 0), date(2, 15)),
+            mock_transaction("Aufwand", "Kasse", AmountEntity::new(30, 0), date(3, 15)),
+            mock_transaction("Kasse", "Ertrag", AmountEntity::new(1000, 0), date(9, 15)),
+        ];
+        let income_statement = income_statement(&transactions)?;
+
+        assert_eq!(income_statement.revenue_total(), AmountEntity::new(80, 0));
+        assert_eq!(income_statement.expense_total(), AmountEntity::new(30, 0));
+        assert_eq!(income_statement.profit(), AmountEntity::new(50, 0));
+        assert!(income_statement.to_string().contains("Profit"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_loss() -> Result<()> {
+        let transactions = vec![mock_transaction(
+            "Aufwand",
+            "Kasse",
+            AmountEntity::new(30, 0),
+            date(3, 15),
+        )];
+        let income_statement = income_statement(&transactions)?;
+
+        assert_eq!(income_statement.profit(), AmountEntity::new(-30, 0));
+        assert!(income_statement.to_string().contains("Loss"));
+        Ok(())
+    }
+}
@@ -0,0 +1,288 @@
+use super::account::{find_account, LedgerAccount};
+use crate::model::entity::{AmountEntity, PostingLine, PostingSide, TransactionAccountReader};
+use crate::model::error::Result;
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct JournalEntry {
+    date: NaiveDate,
+    description: String,
+    lines: Vec<PostingLine>,
+}
+
+impl JournalEntry {
+    fn from(transaction: &dyn TransactionAccountReader) -> Self {
+        JournalEntry {
+            date: transaction.read_date(),
+            description: transaction.read_description().to_string(),
+            lines: transaction.read_lines(),
+        }
+    }
+
+    pub fn date(&self) -> NaiveDate {
+        self.date
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn lines(&self) -> &[PostingLine] {
+        &self.lines
+    }
+
+    /// Debit and credit line if the entry books one amount from one account to another.
+    pub fn single_booking(&self) -> Option<(&PostingLine, &PostingLine)> {
+        match &self.lines[..] {
+            [debit, credit]
+                if debit.side == PostingSide::Debit
+                    && credit.side == PostingSide::Credit
+                    && debit.amount == credit.amount
+                    && debit.currency == credit.currency =>
+            {
+                Some((debit, credit))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Restricts the journal to entries within the date range, booking onto the account
+/// and containing the description text, ignoring case. Unset criteria match every entry.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct JournalFilter {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub account: Option<String>,
+    pub description: Option<String>,
+}
+
+impl JournalFilter {
+    fn matches(&self, entry: &JournalEntry, accounts: &HashMap<String, LedgerAccount>) -> bool {
+        let resolve = |reference: &str| {
+            find_account(accounts, reference)
+                .map(|account| account.name().to_string())
+                .unwrap_or_else(|| reference.to_string())
+        };
+        self.from.is_none_or(|from| entry.date >= from)
+            && self.to.is_none_or(|to| entry.date <= to)
+            && self.account.as_deref().is_none_or(|account| {
+                let account = resolve(account);
+                entry
+                    .lines
+                    .iter()
+                    .any(|line| resolve(&line.account) == account)
+            })
+            && self.description.as_deref().is_none_or(|text| {
+                entry
+                    .description
+                    .to_lowercase()
+                    .contains(&text.to_lowercase())
+            })
+    }
+}
+
+/// Amount of the line, prefixed with its currency if one is set.
+fn format_amount(line: &PostingLine) -> String {
+    match &line.currency {
+        Some(currency) => format!("{currency} {}", line.amount),
+        None => line.amount.to_string(),
+    }
+}
+
+/// General journal listing the transactions of the ledger in chronological order.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Journal {
+    entries: Vec<JournalEntry>,
+    totals: BTreeMap<Option<String>, AmountEntity>,
+}
+
+impl Journal {
+    /// Entries are sorted by date, transactions of the same day keep their order in the ledger.
+    pub fn from(
+        transactions: &[Box<dyn TransactionAccountReader>],
+        accounts: &HashMap<String, LedgerAccount>,
+        filter: &JournalFilter,
+    ) -> Result<Self> {
+        let mut entries: Vec<JournalEntry> = transactions
+            .iter()
+            .map(|transaction| JournalEntry::from(transaction.as_ref()))
+            .filter(|entry| filter.matches(entry, accounts))
+            .collect();
+        entries.sort_by_key(|entry| entry.date);
+        let mut totals = BTreeMap::new();
+        for line in entries.iter().flat_map(|entry| entry.lines.iter()) {
+            if line.side == PostingSide::Debit {
+                let total: &mut AmountEntity = totals.entry(line.currency.clone()).or_default();
+                *total = total.checked_add(line.amount)?;
+            }
+        }
+        Ok(Journal { entries, totals })
+    }
+
+    pub fn entries(&self) -> &[JournalEntry] {
+        &self.entries
+    }
+
+    /// Debited amounts of all entries per currency, `None` stands for the base currency.
+    pub fn totals(&self) -> &BTreeMap<Option<String>, AmountEntity> {
+        &self.totals
+    }
+}
+
+impl Display for Journal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:<12}{:<30}{:<16}{:<16}{:>14}",
+            "Date", "Description", "Debit", "Credit", "Amount"
+        )?;
+        for entry in self.entries.iter() {
+            if let Some((debit, credit)) = entry.single_booking() {
+                writeln!(
+                    f,
+                    "{:<12}{:<30}{:<16}{:<16}{:>14}",
+                    entry.date.to_string(),
+                    entry.description,
+                    debit.account,
+                    credit.account,
+                    format_amount(debit)
+                )?;
+                continue;
+            }
+            writeln!(f, "{:<12}{:<30}", entry.date.to_string(), entry.description)?;
+            for line in entry.lines.iter() {
+                let (debit, credit) = match line.side {
+                    PostingSide::Debit => (line.account.as_str(), ""),
+                    PostingSide::Credit => ("", line.account.as_str()),
+                };
+                writeln!(
+                    f,
+                    "{:<42}{:<16}{:<16}{:>14}",
+                    "",
+                    debit,
+                    credit,
+                    format_amount(line)
+                )?;
+            }
+        }
+        writeln!(f, "{}", "-".repeat(88))?;
+        let totals = self.totals();
+        if totals.is_empty() {
+            writeln!(f, "{:<74}{:>14}", "Total", AmountEntity::zero().to_string())?;
+        }
+        for (currency, total) in totals {
+            let total = match currency {
+                Some(currency) => format!("{currency} {total}"),
+                None => total.to_string(),
+            };
+            writeln!(f, "{:<74}{:>14}", "Total", total)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Journal, JournalFilter};
+    use crate::model::core::test_support::{date, ledger_accounts, mock_described_transaction};
+    use crate::model::entity::{AmountEntity, PostingLine};
+    use crate::model::error::Result;
+    use std::collections::HashMap;
+
+    fn booking(debitor: &str, creditor: &str, amount: i64) -> Vec<PostingLine> {
+        vec![
+            PostingLine::debit(debitor, AmountEntity::new(amount, 0)),
+            PostingLine::credit(creditor, AmountEntity::new(amount, 0)),
+        ]
+    }
+
+    #[test]
+    fn test_journal_lists_transactions() -> Result<()> {
+        let transactions = vec![mock_described_transaction(
+            vec![
+                PostingLine::debit("Kasse", AmountEntity::new(12, 50)),
+                PostingLine::credit("Ertrag", AmountEntity::new(12, 50)),
+            ],
+            "Verkauf",
+            date(3, 1),
+        )];
+
+        let journal = Journal::from(&transactions, &HashMap::new(), &JournalFilter::default())?;
+
+        assert_eq!(journal.entries().len(), 1);
+        let (debit, credit) = journal.entries()[0]
+            .single_booking()
+            .expect("single booking");
+        assert_eq!(debit.account, "Kasse");
+        assert_eq!(credit.account, "Ertrag");
+        assert!(journal.to_string().contains("2022-03-01"));
+        assert!(journal.to_string().contains("12.50"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_journal_lists_compound_lines() -> Result<()> {
+        let transactions = vec![mock_described_transaction(
+            vec![
+                PostingLine::debit("Kasse", AmountEntity::new(10, 0)),
+                PostingLine::debit("Bank", AmountEntity::new(5, 0)),
+                PostingLine::credit("Ertrag", AmountEntity::new(15, 0)),
+            ],
+            "Verkauf",
+            date(3, 1),
+        )];
+
+        let journal = Journal::from(&transactions, &HashMap::new(), &JournalFilter::default())?;
+
+        assert_eq!(journal.entries()[0].single_booking(), None);
+        assert_eq!(journal.to_string().lines().count(), 7);
+        Ok(())
+    }
+
+    #[test]
+    fn test_journal_sorted_by_date() -> Result<()> {
+        let transactions = vec![
+            mock_described_transaction(booking("Kasse", "Ertrag", 10), "Zweite", date(5, 1)),
+            mock_described_transaction(booking("Kasse", "Ertrag", 20), "Erste", date(2, 1)),
+            mock_described_transaction(booking("Bank", "Ertrag", 30), "Dritte", date(5, 1)),
+        ];
+
+        let journal = Journal::from(&transactions, &HashMap::new(), &JournalFilter::default())?;
+
+        let descriptions: Vec<&str> = journal
+            .entries()
+            .iter()
+            .map(|entry| entry.description())
+            .collect();
+        assert_eq!(descriptions, vec!["Erste", "Zweite", "Dritte"]);
+        assert_eq!(journal.totals()[&None], AmountEntity::new(60, 0));
+        assert!(journal.to_string().contains("60.00"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_journal_filter() -> Result<()> {
+        let transactions = vec![
+            mock_described_transaction(booking("1000", "Ertrag", 10), "Verkauf Laden", date(2, 1)),
+            mock_described_transaction(booking("Bank", "Ertrag", 20), "Verkauf Online", date(3, 1)),
+            mock_described_transaction(booking("Kasse", "Ertrag", 30), "Verkauf Laden", date(6, 1)),
+            mock_described_transaction(booking("Aufwand", "Kasse", 40), "Miete", date(4, 1)),
+        ];
+        let filter = JournalFilter {
+            from: Some(date(2, 1)),
+            to: Some(date(5, 31)),
+            account: Some("Kasse".to_string()),
+            description: Some("verkauf".to_string()),
+        };
+
+        let journal = Journal::from(&transactions, &ledger_accounts([]), &filter)?;
+
+        assert_eq!(journal.entries().len(), 1);
+        assert_eq!(journal.entries()[0].description(), "Verkauf Laden");
+        assert_eq!(journal.totals()[&None], AmountEntity::new(10, 0));
+        Ok(())
+    }
+}
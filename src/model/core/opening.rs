@@ -0,0 +1,102 @@
+use super::account::{AccountType, BalanceType, LedgerAccount};
+use super::currency::Currencies;
+use crate::model::entity::AmountEntity;
+use crate::model::error::{AccError, OpeningProblem, Result};
+use std::collections::HashMap;
+
+/// Opening active balances have to equal opening passive balances and income accounts have to start at zero.
+/// Foreign currency accounts count with their opening value in the base currency.
+pub fn check_opening_balances(
+    accounts: &HashMap<String, LedgerAccount>,
+    currencies: &Currencies,
+) -> Result<()> {
+    let mut problems = vec![];
+    let mut active = AmountEntity::zero();
+    let mut passive = AmountEntity::zero();
+    let mut sorted: Vec<&LedgerAccount> = accounts.values().collect();
+    sorted.sort_by(|a, b| a.cmp_by_number(b));
+    for account in sorted {
+        let start = currencies.opening_value(account)?;
+        match account.account_type() {
+            AccountType::Balance(BalanceType::Active(_)) => active = active.checked_add(start)?,
+            AccountType::Balance(BalanceType::Passive(_)) => {
+                passive = passive.checked_add(start)?
+            }
+            AccountType::Income(_) if !start.is_zero() => {
+                problems.push(OpeningProblem::IncomeStart {
+                    account: account.name().to_string(),
+                    start,
+                })
+            }
+            AccountType::Income(_) => {}
+        }
+    }
+    if active != passive {
+        problems.insert(0, OpeningProblem::Unbalanced { active, passive });
+    }
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(AccError::InvalidOpeningBalances(problems))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::check_opening_balances;
+    use crate::model::core::account::{
+        AccountType, ActiveType, BalanceType, IncomeType, LedgerAccount, PassiveType,
+    };
+    use crate::model::core::currency::Currencies;
+    use crate::model::entity::AmountEntity;
+    use crate::model::error::{AccError, OpeningProblem};
+    use std::collections::HashMap;
+
+    fn accounts(equity: i64, revenue: i64) -> HashMap<String, LedgerAccount> {
+        [
+            LedgerAccount::new(
+                "Kasse",
+                AmountEntity::new(1000, 0),
+                AccountType::Balance(BalanceType::Active(ActiveType::WorkingCapital)),
+            ),
+            LedgerAccount::new(
+                "Eigenkapital",
+                AmountEntity::new(equity, 0),
+                AccountType::Balance(BalanceType::Passive(PassiveType::Equity)),
+            ),
+            LedgerAccount::new(
+                "Ertrag",
+                AmountEntity::new(revenue, 0),
+                AccountType::Income(IncomeType::Revenue),
+            ),
+        ]
+        .into_iter()
+        .map(|account| (account.name().to_string(), account))
+        .collect()
+    }
+
+    #[test]
+    fn test_consistent_opening_balances() {
+        assert!(check_opening_balances(&accounts(1000, 0), &Currencies::default()).is_ok());
+    }
+
+    #[test]
+    fn test_inconsistent_opening_balances() {
+        match check_opening_balances(&accounts(800, 50), &Currencies::default()) {
+            Err(AccError::InvalidOpeningBalances(problems)) => assert_eq!(
+                problems,
+                vec![
+                    OpeningProblem::Unbalanced {
+                        active: AmountEntity::new(1000, 0),
+                        passive: AmountEntity::new(800, 0),
+                    },
+                    OpeningProblem::IncomeStart {
+                        account: "Ertrag".to_string(),
+                        start: AmountEntity::new(50, 0),
+                    },
+                ]
+            ),
+            other => panic!("expected invalid opening balances, got {other:?}"),
+        }
+    }
+}
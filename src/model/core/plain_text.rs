@@ -0,0 +1,485 @@
+use super::account::{
+    find_account, AccountType, ActiveType, BalanceType, IncomeType, LedgerAccount, PassiveType,
+};
+use super::currency::Currencies;
+use super::transaction::book_transactions;
+use crate::model::entity::{AmountEntity, Entity, PostingSide, TransactionAccountReader};
+use crate::model::error::Result;
+use chrono::NaiveDate;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+/// Name of the opening entry and of the equity account balancing it.
+pub const OPENING_BALANCES: &str = "Opening Balances";
+pub const OPENING_ACCOUNT: &str = "Equity:Opening Balances";
+
+/// Top-level account of ledger-cli and hledger journals the account group maps to.
+pub fn account_prefix(account_type: &AccountType) -> &'static str {
+    match account_type {
+        AccountType::Balance(BalanceType::Active(ActiveType::WorkingCapital)) => {
+            "Assets:WorkingCapital"
+        }
+        AccountType::Balance(BalanceType::Active(ActiveType::FixedAssets)) => "Assets:FixedAssets",
+        AccountType::Balance(BalanceType::Passive(PassiveType::Equity)) => "Equity",
+        AccountType::Balance(BalanceType::Passive(PassiveType::DebtCapital)) => {
+            "Liabilities:DebtCapital"
+        }
+        AccountType::Income(IncomeType::Revenue) => "Income",
+        AccountType::Income(IncomeType::Expense) => "Expenses",
+    }
+}
+
+fn journal_account(account: &LedgerAccount) -> String {
+    format!(
+        "{}:{}",
+        account_prefix(account.account_type()),
+        account.name()
+    )
+}
+
+#[derive(Clone, PartialEq, Debug)]
+struct PlainTextPosting {
+    account: String,
+    /// Amount with its commodity, debits are positive and credits negative.
+    amount: String,
+    /// Realized exchange difference, tagged so the import leaves it to the booking of the foreign amount.
+    exchange_difference: bool,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+struct PlainTextEntry {
+    date: NaiveDate,
+    cleared: bool,
+    description: String,
+    reference: Option<String>,
+    postings: Vec<PlainTextPosting>,
+    /// Whether a posting without amount balances the entry.
+    balanced_by_opening_account: bool,
+}
+
+/// Ledger in the plain text journal format of ledger-cli and hledger.
+/// Account groups become account prefixes, e.g. `Assets:WorkingCapital:Kasse`.
+/// Foreign currency postings carry their value in the base currency as total cost (`@@`),
+/// so every entry balances in the base currency like in the ledger. The `fx-account` and the
+/// realized exchange differences are tagged, so the journal can be imported again.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PlainTextJournal {
+    name: String,
+    /// Account directives, the `fx-account` with its tag.
+    accounts: Vec<String>,
+    /// Exchange rates as price directives.
+    prices: Vec<String>,
+    entries: Vec<PlainTextEntry>,
+}
+
+impl PlainTextJournal {
+    /// Writes the start amounts as opening entry dated `opening_date`, followed by the booked
+    /// `transactions` in date order. They have to be in the order of the ledger transactions,
+    /// which provide the reference and whether a transaction is reconciled.
+    /// Realized exchange differences are written as postings onto the `fx-account`.
+    pub fn from(
+        ledger: &Entity,
+        transactions: &[Box<dyn TransactionAccountReader>],
+        accounts: &HashMap<String, LedgerAccount>,
+        currencies: &Currencies,
+        opening_date: NaiveDate,
+    ) -> Result<Self> {
+        let mut sorted: Vec<&LedgerAccount> = accounts.values().collect();
+        sorted.sort_by(|a, b| a.cmp_by_number(b));
+        let amount = |amount: AmountEntity, currency: Option<&str>, value: AmountEntity| {
+            if currencies.is_base(currency) {
+                format!("{value} {}", currencies.base())
+            } else {
+                format!(
+                    "{amount} {} @@ {} {}",
+                    currencies.code(currency),
+                    value.abs(),
+                    currencies.base()
+                )
+            }
+        };
+        let signed = |increases_on_debit: bool, amount: AmountEntity| {
+            if increases_on_debit {
+                amount
+            } else {
+                -amount
+            }
+        };
+        let mut opening_postings = vec![];
+        let mut opening_values = vec![];
+        for account in sorted.iter().filter(|account| !account.start().is_zero()) {
+            let increases_on_debit = account.account_type().increases_on_debit();
+            let value = signed(increases_on_debit, currencies.opening_value(account)?);
+            opening_values.push(value);
+            opening_postings.push(PlainTextPosting {
+                account: journal_account(account),
+                amount: amount(
+                    signed(increases_on_debit, account.start()),
+                    account.currency(),
+                    value,
+                ),
+                exchange_difference: false,
+            });
+        }
+        let opening_balanced = AmountEntity::checked_sum(opening_values)?.is_zero();
+        let mut entries = vec![];
+        if !opening_postings.is_empty() {
+            entries.push(PlainTextEntry {
+                date: opening_date,
+                cleared: true,
+                description: OPENING_BALANCES.to_string(),
+                reference: None,
+                postings: opening_postings,
+                balanced_by_opening_account: !opening_balanced,
+            });
+        }
+        let mut postings: Vec<Vec<PlainTextPosting>> = vec![vec![]; transactions.len()];
+        book_transactions(
+            transactions,
+            accounts,
+            currencies,
+            |_| true,
+            |booking| {
+                let Some(account) = find_account(accounts, &booking.account) else {
+                    return;
+                };
+                let value = signed(booking.side == PostingSide::Debit, booking.amount);
+                let posting = match booking.line {
+                    Some(line) => {
+                        let line = &transactions[booking.transaction].read_lines()[line];
+                        let foreign = signed(line.side == PostingSide::Debit, line.amount);
+                        amount(foreign, account.currency(), value)
+                    }
+                    None => amount(value, None, value),
+                };
+                postings[booking.transaction].push(PlainTextPosting {
+                    account: journal_account(account),
+                    amount: posting,
+                    exchange_difference: booking.line.is_none(),
+                });
+            },
+        )?;
+        let mut booked: Vec<PlainTextEntry> = transactions
+            .iter()
+            .zip(ledger.transactions.iter())
+            .zip(postings)
+            .map(|((transaction, entity), postings)| PlainTextEntry {
+                date: transaction.read_date(),
+                cleared: entity.is_reconciled(),
+                description: transaction.read_description().to_string(),
+                reference: entity.reference().map(str::to_string),
+                postings,
+                balanced_by_opening_account: false,
+            })
+            .collect();
+        booked.sort_by_key(|entry| entry.date);
+        entries.extend(booked);
+        Ok(PlainTextJournal {
+            name: ledger.name().to_string(),
+            accounts: sorted
+                .into_iter()
+                .map(|account| {
+                    let fx_account = currencies
+                        .fx_account()
+                        .and_then(|fx_account| find_account(accounts, fx_account))
+                        .is_some_and(|fx_account| fx_account.name() == account.name());
+                    if fx_account {
+                        format!("{}  ; fx-account:", journal_account(account))
+                    } else {
+                        journal_account(account)
+                    }
+                })
+                .collect(),
+            prices: ledger
+                .definition
+                .currency()
+                .rates
+                .iter()
+                .map(|rate| {
+                    format!(
+                        "P {} {} {} {}",
+                        rate.date.format("%Y-%m-%d"),
+                        rate.currency,
+                        rate.rate,
+                        currencies.base()
+                    )
+                })
+                .collect(),
+            entries,
+        })
+    }
+}
+
+impl Display for PlainTextJournal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "; {}", self.name)?;
+        writeln!(f)?;
+        for account in self.accounts.iter() {
+            writeln!(f, "account {account}")?;
+        }
+        if !self.prices.is_empty() {
+            writeln!(f)?;
+        }
+        for price in self.prices.iter() {
+            writeln!(f, "{price}")?;
+        }
+        for entry in self.entries.iter() {
+            writeln!(f)?;
+            write!(f, "{}", entry.date.format("%Y-%m-%d"))?;
+            if entry.cleared {
+                write!(f, " *")?;
+            }
+            write!(f, " {}", entry.description)?;
+            if let Some(reference) = &entry.reference {
+                write!(f, "  ; reference: {reference}")?;
+            }
+            writeln!(f)?;
+            for posting in entry.postings.iter() {
+                write!(f, "    {:<46}  {:>18}", posting.account, posting.amount)?;
+                if posting.exchange_difference {
+                    write!(f, "  ; exchange-difference:")?;
+                }
+                writeln!(f)?;
+            }
+            if entry.balanced_by_opening_account {
+                writeln!(f, "    {OPENING_ACCOUNT}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PlainTextJournal;
+    use crate::model::core::account::from_ledger_definition;
+    use crate::model::core::currency::{check_currencies, Currencies};
+    use crate::model::core::opening::check_opening_balances;
+    use crate::model::core::plain_text_import::{import_journal_file, AccountPrefixes};
+    use crate::model::core::transaction::calculate_transactions;
+    use crate::model::entity::{read_journal_file, Entity, TransactionAccountReader};
+    use crate::model::error::Result;
+    use chrono::NaiveDate;
+
+    const LEDGER: &str = r#"
+name: Buchhaltung 2022
+definition:
+  balance:
+    active:
+      working-capital:
+        - name: Kasse
+          number: 1000
+          start: 1000
+          accounts:
+            - name: Hauptkasse
+      fixed-assets: []
+    passive:
+      equity:
+        - name: Eigenkapital
+          number: 2800
+          start: 1000
+      debt-capital: []
+  income:
+    revenue:
+      - name: Ertrag
+        number: 3000
+    expense: []
+transactions:
+  - date: 2022-03-01
+    deb: Kasse:Hauptkasse
+    cred: 3000
+    amount: 50
+    description: Verkauf
+    reference: REF-1
+    reconciled: true
+  - date: 2022-02-01
+    deb: Kasse:Hauptkasse
+    cred: Ertrag
+    amount: 20
+    description: Barverkauf
+"#;
+
+    #[test]
+    fn test_plain_text_journal() -> Result<()> {
+        let ledger: Entity = serde_yaml::from_str(LEDGER)?;
+        let accounts = from_ledger_definition(&ledger.definition)?;
+        let transactions: Vec<Box<dyn TransactionAccountReader>> = ledger
+            .transactions
+            .iter()
+            .cloned()
+            .map(|transaction| Box::new(transaction) as Box<dyn TransactionAccountReader>)
+            .collect();
+
+        let journal = PlainTextJournal::from(
+            &ledger,
+            &transactions,
+            &accounts,
+            &Currencies::default(),
+            NaiveDate::from_ymd_opt(2022, 1, 1).unwrap(),
+        )?
+        .to_string();
+
+        let lines: Vec<&str> = journal.lines().map(str::trim_end).collect();
+        assert!(lines.contains(&"account Assets:WorkingCapital:Kasse:Hauptkasse"));
+        let opening = lines
+            .iter()
+            .position(|line| *line == "2022-01-01 * Opening Balances")
+            .unwrap();
+        assert!(lines[opening + 1].starts_with("    Assets:WorkingCapital:Kasse "));
+        assert!(lines[opening + 1].ends_with(" 1000.00 CHF"));
+        assert!(lines[opening + 2].ends_with("-1000.00 CHF"));
+        let cash_sale = lines
+            .iter()
+            .position(|line| *line == "2022-02-01 Barverkauf");
+        let sale = lines
+            .iter()
+            .position(|line| *line == "2022-03-01 * Verkauf  ; reference: REF-1")
+            .unwrap();
+        assert!(cash_sale.unwrap() < sale);
+        assert!(lines[sale + 1].starts_with("    Assets:WorkingCapital:Kasse:Hauptkasse "));
+        assert!(lines[sale + 2].starts_with("    Income:Ertrag "));
+        assert!(lines[sale + 2].ends_with("-50.00 CHF"));
+        assert!(!journal.contains("Equity:Opening Balances"));
+        Ok(())
+    }
+
+    const FOREIGN_LEDGER: &str = r#"
+name: Buchhaltung 2022
+definition:
+  balance:
+    active:
+      working-capital:
+        - name: Bank EUR
+          number: 1020
+          currency: EUR
+          start: 1000
+      fixed-assets: []
+    passive:
+      equity:
+        - name: Eigenkapital
+          number: 2800
+          start: 1000
+      debt-capital: []
+  income:
+    revenue:
+      - name: Ertrag
+        number: 3000
+      - name: Kursdifferenzen
+        number: 6950
+    expense: []
+  currency:
+    base: CHF
+    fx-account: Kursdifferenzen
+    rates:
+      - currency: EUR
+        date: 2022-01-01
+        rate: 1.0
+      - currency: EUR
+        date: 2022-06-01
+        rate: 0.95
+transactions:
+  - date: 2022-07-01
+    deb: Ertrag
+    cred: Bank EUR
+    amount: 100
+    currency: EUR
+    description: Gutschrift
+"#;
+
+    #[test]
+    fn test_plain_text_journal_in_foreign_currency() -> Result<()> {
+        let ledger: Entity = serde_yaml::from_str(FOREIGN_LEDGER)?;
+        let accounts = from_ledger_definition(&ledger.definition)?;
+        let transactions: Vec<Box<dyn TransactionAccountReader>> = ledger
+            .transactions
+            .iter()
+            .cloned()
+            .map(|transaction| Box::new(transaction) as Box<dyn TransactionAccountReader>)
+            .collect();
+
+        let journal = PlainTextJournal::from(
+            &ledger,
+            &transactions,
+            &accounts,
+            &Currencies::from(ledger.definition.currency()),
+            NaiveDate::from_ymd_opt(2022, 1, 1).unwrap(),
+        )?
+        .to_string();
+
+        let lines: Vec<&str> = journal.lines().map(str::trim_end).collect();
+        assert!(lines.contains(&"P 2022-06-01 EUR 0.95 CHF"));
+        let opening = lines
+            .iter()
+            .position(|line| *line == "2022-01-01 * Opening Balances")
+            .unwrap();
+        assert!(lines[opening + 1].starts_with("    Assets:WorkingCapital:Bank EUR "));
+        assert!(lines[opening + 1].ends_with(" 1000.00 EUR @@ 1000.00 CHF"));
+        assert!(lines[opening + 2].ends_with("-1000.00 CHF"));
+        let transfer = lines
+            .iter()
+            .position(|line| *line == "2022-07-01 Gutschrift")
+            .unwrap();
+        assert!(lines[transfer + 1].starts_with("    Income:Ertrag "));
+        assert!(lines[transfer + 1].ends_with(" 95.00 CHF"));
+        assert!(lines[transfer + 2].starts_with("    Assets:WorkingCapital:Bank EUR "));
+        assert!(lines[transfer + 2].ends_with(" -100.00 EUR @@ 100.00 CHF"));
+        assert!(lines[transfer + 3].starts_with("    Income:Kursdifferenzen "));
+        assert!(lines[transfer + 3].ends_with(" 5.00 CHF  ; exchange-difference:"));
+        assert!(lines.contains(&"account Income:Kursdifferenzen  ; fx-account:"));
+        assert!(!journal.contains("Equity:Opening Balances"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_plain_text_journal_imported_again() -> Result<()> {
+        let ledger: Entity = serde_yaml::from_str(FOREIGN_LEDGER)?;
+        let accounts = from_ledger_definition(&ledger.definition)?;
+        let currencies = Currencies::from(ledger.definition.currency());
+        let transactions: Vec<Box<dyn TransactionAccountReader>> = ledger
+            .transactions
+            .iter()
+            .cloned()
+            .map(|transaction| Box::new(transaction) as Box<dyn TransactionAccountReader>)
+            .collect();
+        let journal = PlainTextJournal::from(
+            &ledger,
+            &transactions,
+            &accounts,
+            &currencies,
+            NaiveDate::from_ymd_opt(2022, 1, 1).unwrap(),
+        )?
+        .to_string();
+
+        let imported = import_journal_file(
+            ledger.name().to_string(),
+            &read_journal_file(journal.as_bytes())?,
+            &AccountPrefixes::default(),
+            "CHF",
+        )?;
+
+        let imported_accounts = from_ledger_definition(&imported.definition)?;
+        let imported_currencies = Currencies::from(imported.definition.currency());
+        assert_eq!(imported.definition.currency(), ledger.definition.currency());
+        assert_eq!(imported_accounts["Bank EUR"].currency(), Some("EUR"));
+        check_currencies(&imported_accounts, &imported_currencies)?;
+        check_opening_balances(&imported_accounts, &imported_currencies)?;
+        let imported_transactions: Vec<Box<dyn TransactionAccountReader>> = imported
+            .transactions
+            .iter()
+            .cloned()
+            .map(|transaction| Box::new(transaction) as Box<dyn TransactionAccountReader>)
+            .collect();
+        let expected = calculate_transactions(&transactions, &accounts, &currencies)?;
+        let result = calculate_transactions(
+            &imported_transactions,
+            &imported_accounts,
+            &imported_currencies,
+        )?;
+        for account in expected.accounts() {
+            let imported_account = result.get_account(account.name()).unwrap();
+            assert_eq!(imported_account.current_amount(), account.current_amount());
+            assert_eq!(imported_account.foreign_amount(), account.foreign_amount());
+        }
+        Ok(())
+    }
+}
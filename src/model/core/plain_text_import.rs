@@ -0,0 +1,756 @@
+use super::account::{
+    from_ledger_definition, AccountType, ActiveType, BalanceType, IncomeType, PassiveType,
+};
+use super::plain_text::{account_prefix, OPENING_ACCOUNT, OPENING_BALANCES};
+use crate::model::entity::{
+    AccountEntity, AccountGroups, AmountEntity, CurrencyEntity, Entity, JournalFile,
+    JournalFileCost, JournalFileEntry, JournalFilePosting, LedgerEntity, PostingLine, RateEntity,
+    TransactionEntity,
+};
+use crate::model::error::{AccError, EntryError, EntryProblem, Result};
+use std::collections::{BTreeSet, HashMap};
+
+/// Top-level accounts of a journal and the account group of the accounts below them.
+#[derive(Clone, PartialEq, Debug)]
+pub struct AccountPrefixes {
+    prefixes: Vec<(String, AccountType)>,
+}
+
+impl Default for AccountPrefixes {
+    /// `Assets`, `Liabilities`, `Equity`, `Income` and `Expenses`, as well as the prefixes of the export.
+    fn default() -> Self {
+        let mut prefixes = AccountPrefixes { prefixes: vec![] }
+            .with(
+                "Assets",
+                AccountType::Balance(BalanceType::Active(ActiveType::WorkingCapital)),
+            )
+            .with(
+                "Liabilities",
+                AccountType::Balance(BalanceType::Passive(PassiveType::DebtCapital)),
+            );
+        for account_type in [
+            AccountType::Balance(BalanceType::Active(ActiveType::WorkingCapital)),
+            AccountType::Balance(BalanceType::Active(ActiveType::FixedAssets)),
+            AccountType::Balance(BalanceType::Passive(PassiveType::Equity)),
+            AccountType::Balance(BalanceType::Passive(PassiveType::DebtCapital)),
+            AccountType::Income(IncomeType::Revenue),
+            AccountType::Income(IncomeType::Expense),
+        ] {
+            prefixes = prefixes.with(account_prefix(&account_type), account_type);
+        }
+        prefixes
+    }
+}
+
+impl AccountPrefixes {
+    /// Maps the accounts below `prefix` to the group of `account_type`, replacing an existing mapping.
+    pub fn with(mut self, prefix: &str, account_type: AccountType) -> Self {
+        let prefix = prefix.trim_end_matches(':');
+        self.prefixes
+            .retain(|(existing, _)| !existing.eq_ignore_ascii_case(prefix));
+        self.prefixes.push((prefix.to_string(), account_type));
+        self
+    }
+
+    /// Group and name below the longest matching prefix, ignoring case.
+    fn split<'a>(&self, account: &'a str) -> Option<(&AccountType, &'a str)> {
+        self.prefixes
+            .iter()
+            .filter_map(|(prefix, account_type)| {
+                let head = account.get(..prefix.len())?;
+                let name = account[prefix.len()..].strip_prefix(':')?;
+                (head.eq_ignore_ascii_case(prefix) && !name.is_empty()).then_some((
+                    prefix.len(),
+                    account_type,
+                    name,
+                ))
+            })
+            .max_by_key(|(length, ..)| *length)
+            .map(|(_, account_type, name)| (account_type, name))
+    }
+}
+
+struct ImportedAccount {
+    /// Name below the prefix, which becomes the name in the ledger.
+    name: String,
+    /// Name in the journal, including the prefix.
+    journal_name: String,
+    account_type: AccountType,
+    start: AmountEntity,
+    /// Value of the start amount in base currency, if it was given as total cost.
+    start_value: Option<AmountEntity>,
+    currencies: BTreeSet<String>,
+}
+
+impl ImportedAccount {
+    /// Balance accounts only booked in one foreign currency are kept in it.
+    fn currency(&self, base_currency: &str) -> Option<String> {
+        match (
+            &self.account_type,
+            self.currencies.len(),
+            self.currencies.first(),
+        ) {
+            (AccountType::Balance(_), 1, Some(currency)) if currency != base_currency => {
+                Some(currency.clone())
+            }
+            _ => None,
+        }
+    }
+
+    /// Adds `amount` to the start amount, debits are positive.
+    fn add_start(&mut self, amount: AmountEntity, value: Option<AmountEntity>) -> Result<()> {
+        let signed = |amount: AmountEntity| {
+            if self.account_type.increases_on_debit() {
+                amount
+            } else {
+                -amount
+            }
+        };
+        let start = self.start.checked_add(signed(amount))?;
+        let start_value = match value {
+            Some(value) => Some(
+                self.start_value
+                    .unwrap_or_default()
+                    .checked_add(signed(value))?,
+            ),
+            None => self.start_value,
+        };
+        self.start = start;
+        self.start_value = start_value;
+        Ok(())
+    }
+}
+
+/// Accounts of the journal in order of their first appearance.
+struct ImportedAccounts<'a> {
+    prefixes: &'a AccountPrefixes,
+    accounts: Vec<ImportedAccount>,
+    /// Position in `accounts` by the name below the prefix.
+    positions: HashMap<String, usize>,
+    /// Name of the account tagged as `fx-account` or booked with realized exchange differences.
+    fx_account: Option<String>,
+}
+
+impl ImportedAccounts<'_> {
+    /// The account of the journal account, added on its first appearance.
+    fn resolve(
+        &mut self,
+        journal_name: &str,
+    ) -> std::result::Result<&mut ImportedAccount, EntryProblem> {
+        let (account_type, name) = self
+            .prefixes
+            .split(journal_name)
+            .ok_or_else(|| EntryProblem::UnknownPrefix(journal_name.to_string()))?;
+        let position = match self.positions.get(name) {
+            Some(position) => *position,
+            None => {
+                self.positions.insert(name.to_string(), self.accounts.len());
+                self.accounts.push(ImportedAccount {
+                    name: name.to_string(),
+                    journal_name: journal_name.to_string(),
+                    account_type: account_type.clone(),
+                    start: AmountEntity::zero(),
+                    start_value: None,
+                    currencies: BTreeSet::new(),
+                });
+                self.accounts.len() - 1
+            }
+        };
+        let account = &mut self.accounts[position];
+        if &account.account_type != account_type {
+            return Err(EntryProblem::DuplicateAccount {
+                account: journal_name.to_string(),
+                other: account.journal_name.clone(),
+            });
+        }
+        Ok(account)
+    }
+
+    fn has_sub_accounts(&self, name: &str) -> bool {
+        let parent = format!("{name}:");
+        self.accounts
+            .iter()
+            .any(|other| other.name.starts_with(&parent))
+    }
+}
+
+/// Posting of an entry with the account name below its prefix and the amount of an elided posting filled in.
+#[derive(Clone)]
+struct ResolvedPosting {
+    account: String,
+    journal_account: String,
+    amount: AmountEntity,
+    currency: String,
+    /// Value of the amount in base currency, if it is converted with a total cost (`@@`).
+    value: Option<AmountEntity>,
+}
+
+/// Converts the journal into a ledger in `base_currency`. Account groups follow from the prefixes,
+/// the entry described as `Opening Balances` provides the start amounts. Entries with more than two
+/// postings become transactions with posting lines. Every entry which cannot be represented is reported.
+/// Amounts with a total cost in base currency stay in their commodity if their account is kept in it,
+/// otherwise their value is booked. Prices in base currency become the exchange rates.
+pub fn import_journal_file(
+    name: String,
+    journal: &JournalFile,
+    prefixes: &AccountPrefixes,
+    base_currency: &str,
+) -> Result<Entity> {
+    let mut accounts = ImportedAccounts {
+        prefixes,
+        accounts: vec![],
+        positions: HashMap::new(),
+        fx_account: None,
+    };
+    let mut errors = vec![];
+    for declared in journal.accounts.iter() {
+        match accounts.resolve(&declared.name) {
+            Ok(account) if declared.fx_account => {
+                let name = account.name.clone();
+                accounts.fx_account = Some(name);
+            }
+            Ok(_) => {}
+            Err(problem) => errors.push(EntryError {
+                line: declared.line,
+                description: format!("account {}", declared.name),
+                problem,
+            }),
+        }
+    }
+    let mut booked: Vec<(&JournalFileEntry, Vec<ResolvedPosting>)> = vec![];
+    for entry in journal.entries.iter() {
+        match resolve_postings(entry, &mut accounts, base_currency) {
+            Ok(postings) => booked.push((entry, postings)),
+            Err(problems) => errors.extend(problems.into_iter().map(|problem| EntryError {
+                line: entry.line,
+                description: entry.description.clone(),
+                problem,
+            })),
+        }
+    }
+
+    for (entry, postings) in booked.iter() {
+        for posting in postings.iter() {
+            let has_sub_accounts = accounts.has_sub_accounts(&posting.account);
+            let account = match accounts.resolve(&posting.journal_account) {
+                Ok(account) => account,
+                Err(problem) => {
+                    errors.push(EntryError {
+                        line: entry.line,
+                        description: entry.description.clone(),
+                        problem,
+                    });
+                    continue;
+                }
+            };
+            if has_sub_accounts {
+                errors.push(EntryError {
+                    line: entry.line,
+                    description: entry.description.clone(),
+                    problem: EntryProblem::ParentAccount(account.journal_name.clone()),
+                });
+            }
+            account.currencies.insert(posting.currency.clone());
+        }
+    }
+
+    let mut transactions = vec![];
+    for (entry, postings) in booked.iter() {
+        let error = |problem| EntryError {
+            line: entry.line,
+            description: entry.description.clone(),
+            problem,
+        };
+        let opening = entry.description.eq_ignore_ascii_case(OPENING_BALANCES);
+        let mut booked_postings = vec![];
+        for posting in postings.iter() {
+            let Ok(account) = accounts.resolve(&posting.journal_account) else {
+                continue;
+            };
+            let currency = account.currency(base_currency);
+            let posting = match posting.value {
+                Some(value) if currency.as_ref() != Some(&posting.currency) => ResolvedPosting {
+                    amount: value,
+                    currency: base_currency.to_string(),
+                    value: None,
+                    ..posting.clone()
+                },
+                _ => posting.clone(),
+            };
+            if opening {
+                if account.add_start(posting.amount, posting.value).is_err() {
+                    errors.push(error(EntryProblem::AmountOverflow));
+                }
+                if posting.currency != base_currency && currency.as_ref() != Some(&posting.currency)
+                {
+                    errors.push(error(EntryProblem::CurrencyMismatch {
+                        account: account.journal_name.clone(),
+                        currency: posting.currency.clone(),
+                    }));
+                }
+            }
+            booked_postings.push(posting);
+        }
+        if opening {
+            continue;
+        }
+        match to_transaction(entry, &booked_postings, base_currency) {
+            Some(transaction) => transactions.push(transaction),
+            None => errors.push(error(EntryProblem::TooFewPostings)),
+        }
+    }
+    if !errors.is_empty() {
+        errors.sort_by_key(|error| error.line);
+        return Err(AccError::InvalidJournal(errors));
+    }
+
+    let mut groups = AccountGroups::default();
+    for imported in accounts.accounts.iter() {
+        let account = insert_account(group(&mut groups, &imported.account_type), &imported.name);
+        account.start = imported.start;
+        account.currency = imported.currency(base_currency);
+        if account.currency.is_some() {
+            account.start_value = imported.start_value;
+        }
+    }
+    let definition = LedgerEntity::from_groups(
+        groups,
+        CurrencyEntity {
+            base: base_currency.to_string(),
+            fx_account: accounts.fx_account,
+            rates: journal
+                .prices
+                .iter()
+                .filter(|price| price.currency == base_currency)
+                .map(|price| RateEntity {
+                    currency: price.commodity.clone(),
+                    date: price.date,
+                    rate: price.rate,
+                })
+                .collect(),
+        },
+    );
+    from_ledger_definition(&definition)?;
+    Ok(Entity::new(name, definition, transactions))
+}
+
+/// Resolves the accounts and fills in an elided amount. An amount with a total cost balances with its value.
+/// Postings onto the account balancing the opening entry and realized exchange differences are left out.
+fn resolve_postings(
+    entry: &JournalFileEntry,
+    accounts: &mut ImportedAccounts,
+    base_currency: &str,
+) -> std::result::Result<Vec<ResolvedPosting>, Vec<EntryProblem>> {
+    let mut problems = vec![];
+    let opening = entry.description.eq_ignore_ascii_case(OPENING_BALANCES);
+    let currencies: BTreeSet<&str> = entry
+        .postings
+        .iter()
+        .filter(|posting| posting.amount.is_some())
+        .map(|posting| match &posting.cost {
+            Some(JournalFileCost::Total { commodity, .. }) => {
+                commodity.as_deref().unwrap_or(base_currency)
+            }
+            _ => posting.commodity.as_deref().unwrap_or(base_currency),
+        })
+        .collect();
+    let elided: Vec<&str> = entry
+        .postings
+        .iter()
+        .filter(|posting| posting.amount.is_none())
+        .map(|posting| posting.account.as_str())
+        .collect();
+    if elided.len() > 1 {
+        problems.push(EntryProblem::MissingAmounts);
+    }
+    // Opening balances in several currencies are balanced by the opening account.
+    let balanced_by_opening_account = opening
+        && elided
+            .iter()
+            .all(|account| account.eq_ignore_ascii_case(OPENING_ACCOUNT));
+    if currencies.len() > 1 && !balanced_by_opening_account {
+        problems.push(EntryProblem::MixedCurrencies(
+            currencies
+                .iter()
+                .map(|currency| currency.to_string())
+                .collect(),
+        ));
+    }
+    let Ok(total) = AmountEntity::checked_sum(entry.postings.iter().filter_map(balancing_amount))
+    else {
+        problems.push(EntryProblem::AmountOverflow);
+        return Err(problems);
+    };
+    let entry_currency = currencies.first().copied().unwrap_or(base_currency);
+    let mut resolved = vec![];
+    for posting in entry.postings.iter() {
+        let value = match (&posting.cost, posting.amount) {
+            (None, _) => None,
+            (Some(JournalFileCost::Total { commodity, .. }), Some(_))
+                if commodity.as_deref().unwrap_or(base_currency) == base_currency =>
+            {
+                balancing_amount(posting)
+            }
+            _ => {
+                problems.push(EntryProblem::Cost(posting.account.clone()));
+                continue;
+            }
+        };
+        if posting.virtual_posting {
+            problems.push(EntryProblem::VirtualPosting(posting.account.clone()));
+        } else if opening && posting.account.eq_ignore_ascii_case(OPENING_ACCOUNT) {
+            continue;
+        } else {
+            match accounts.resolve(&posting.account) {
+                Ok(account) if posting.exchange_difference => {
+                    let name = account.name.clone();
+                    accounts.fx_account = Some(name);
+                }
+                Ok(account) => resolved.push(ResolvedPosting {
+                    account: account.name.clone(),
+                    journal_account: posting.account.clone(),
+                    amount: posting.amount.unwrap_or(-total),
+                    currency: posting
+                        .commodity
+                        .as_deref()
+                        .unwrap_or(entry_currency)
+                        .to_string(),
+                    value,
+                }),
+                Err(problem) => problems.push(problem),
+            }
+        }
+    }
+    // Unbalanced opening balances are reported when the ledger is validated.
+    if !opening && elided.is_empty() && !total.is_zero() && problems.is_empty() {
+        problems.push(EntryProblem::Unbalanced {
+            difference: total,
+            currency: entry_currency.to_string(),
+        });
+    }
+    if problems.is_empty() {
+        Ok(resolved)
+    } else {
+        Err(problems)
+    }
+}
+
+/// Amount the posting balances the entry with, the signed value of an amount with a total cost.
+fn balancing_amount(posting: &JournalFilePosting) -> Option<AmountEntity> {
+    match (&posting.cost, posting.amount) {
+        (Some(JournalFileCost::Total { amount: value, .. }), Some(amount))
+            if amount.is_negative() =>
+        {
+            Some(-*value)
+        }
+        (Some(JournalFileCost::Total { amount: value, .. }), Some(_)) => Some(*value),
+        (_, amount) => amount,
+    }
+}
+
+/// A transaction from the debited to the credited account if the entry has two postings in the same
+/// currency, otherwise one with posting lines. `None` if less than two postings have an amount.
+fn to_transaction(
+    entry: &JournalFileEntry,
+    postings: &[ResolvedPosting],
+    base_currency: &str,
+) -> Option<TransactionEntity> {
+    let postings: Vec<&ResolvedPosting> = postings
+        .iter()
+        .filter(|posting| !posting.amount.is_zero())
+        .collect();
+    let currency = postings.first()?.currency.clone();
+    let mixed = postings.iter().any(|posting| posting.currency != currency);
+    let mut transaction = match postings[..] {
+        [_] => return None,
+        [first, second] if !mixed => {
+            let (debit, credit) = if first.amount.is_negative() {
+                (second, first)
+            } else {
+                (first, second)
+            };
+            TransactionEntity::new(
+                entry.description.clone(),
+                debit.amount,
+                debit.account.clone(),
+                credit.account.clone(),
+                entry.date,
+            )
+        }
+        _ => TransactionEntity::compound(
+            entry.description.clone(),
+            postings
+                .iter()
+                .map(|posting| {
+                    let line = if posting.amount.is_negative() {
+                        PostingLine::credit(&posting.account, posting.amount.abs())
+                    } else {
+                        PostingLine::debit(&posting.account, posting.amount)
+                    };
+                    if mixed && posting.currency != base_currency {
+                        line.with_currency(&posting.currency)
+                    } else {
+                        line
+                    }
+                })
+                .collect(),
+            entry.date,
+        ),
+    };
+    if !mixed && currency != base_currency {
+        transaction = transaction.with_currency(currency);
+    }
+    if let Some(reference) = &entry.reference {
+        transaction = transaction.with_reference(reference.clone());
+    }
+    if entry.cleared {
+        transaction.mark_reconciled();
+    }
+    Some(transaction)
+}
+
+fn group<'a>(
+    groups: &'a mut AccountGroups,
+    account_type: &AccountType,
+) -> &'a mut Vec<AccountEntity> {
+    match account_type {
+        AccountType::Balance(BalanceType::Active(ActiveType::WorkingCapital)) => {
+            &mut groups.working_capital
+        }
+        AccountType::Balance(BalanceType::Active(ActiveType::FixedAssets)) => {
+            &mut groups.fixed_assets
+        }
+        AccountType::Balance(BalanceType::Passive(PassiveType::Equity)) => &mut groups.equity,
+        AccountType::Balance(BalanceType::Passive(PassiveType::DebtCapital)) => {
+            &mut groups.debt_capital
+        }
+        AccountType::Income(IncomeType::Revenue) => &mut groups.revenue,
+        AccountType::Income(IncomeType::Expense) => &mut groups.expense,
+    }
+}
+
+/// Finds or adds the account with the full `name`, adding missing parent accounts.
+fn insert_account<'a>(accounts: &'a mut Vec<AccountEntity>, name: &str) -> &'a mut AccountEntity {
+    let (head, rest) = match name.split_once(':') {
+        Some((head, rest)) => (head, Some(rest)),
+        None => (name, None),
+    };
+    let index = match accounts.iter().position(|account| account.name == head) {
+        Some(index) => index,
+        None => {
+            accounts.push(AccountEntity {
+                name: head.to_string(),
+                number: None,
+                start: AmountEntity::zero(),
+                start_value: None,
+                currency: None,
+                accounts: vec![],
+            });
+            accounts.len() - 1
+        }
+    };
+    match rest {
+        Some(rest) => insert_account(&mut accounts[index].accounts, rest),
+        None => &mut accounts[index],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{import_journal_file, AccountPrefixes};
+    use crate::model::core::account::{
+        from_ledger_definition, AccountType, BalanceType, PassiveType,
+    };
+    use crate::model::entity::{
+        read_journal_file, AmountEntity, PostingLine, TransactionAccountReader,
+    };
+    use crate::model::error::{AccError, EntryProblem};
+
+    #[test]
+    fn test_import_journal_file() {
+        let journal = r#"
+2022-01-01 * Opening Balances
+    Assets:Kasse:Hauptkasse        1000 CHF
+    Assets:Bank EUR                 500 EUR
+    Equity:Eigenkapital           -1000 CHF
+    Equity:Opening Balances
+
+2022-01-05 * Verkauf  ; reference: REF-1
+    Assets:Kasse:Hauptkasse      108.10 CHF
+    Income:Ertrag               -100.00 CHF
+    Darlehen:Bank
+
+2022-01-06 Zins
+    Expenses:Zins                    20 EUR
+    Assets:Bank EUR
+"#;
+        let file = read_journal_file(journal.as_bytes()).unwrap();
+        let prefixes = AccountPrefixes::default().with(
+            "Darlehen",
+            AccountType::Balance(BalanceType::Passive(PassiveType::DebtCapital)),
+        );
+
+        let ledger =
+            import_journal_file("Buchhaltung".to_string(), &file, &prefixes, "CHF").unwrap();
+
+        let accounts = from_ledger_definition(&ledger.definition).unwrap();
+        assert_eq!(
+            accounts["Kasse:Hauptkasse"].start(),
+            AmountEntity::new(1000, 0)
+        );
+        assert_eq!(accounts["Kasse"].start(), AmountEntity::zero());
+        assert_eq!(accounts["Eigenkapital"].start(), AmountEntity::new(1000, 0));
+        assert_eq!(accounts["Bank EUR"].currency(), Some("EUR"));
+        assert_eq!(accounts["Bank EUR"].start(), AmountEntity::new(500, 0));
+        assert_eq!(
+            accounts["Bank"].account_type(),
+            &AccountType::Balance(BalanceType::Passive(PassiveType::DebtCapital))
+        );
+        assert_eq!(accounts["Zins"].currency(), None);
+        assert!(!accounts.contains_key("Opening Balances"));
+
+        assert_eq!(ledger.transactions.len(), 2);
+        let sale = &ledger.transactions[0];
+        assert_eq!(
+            sale.read_lines(),
+            vec![
+                PostingLine::debit("Kasse:Hauptkasse", AmountEntity::new(108, 10)),
+                PostingLine::credit("Ertrag", AmountEntity::new(100, 0)),
+                PostingLine::credit("Bank", AmountEntity::new(8, 10)),
+            ]
+        );
+        assert_eq!(sale.reference(), Some("REF-1"));
+        assert!(sale.is_reconciled());
+        let interest = ledger.transactions[1].read_lines();
+        assert_eq!(interest[0].account, "Zins");
+        assert_eq!(interest[1].account, "Bank EUR");
+        assert_eq!(interest[1].currency.as_deref(), Some("EUR"));
+    }
+
+    #[test]
+    fn test_import_journal_file_with_total_costs() {
+        let journal = r#"
+P 2022-01-01 EUR 0.98 CHF
+P 2022-01-01 USD 0.91 EUR
+
+2022-01-05 Wechsel
+    Assets:Bank EUR                 100 EUR @@ 98 CHF
+    Assets:Kasse
+
+2022-01-06 Einkauf
+    Expenses:Material                50 EUR @@ 49 CHF
+    Assets:Kasse                    -49 CHF
+
+2022-01-07 Falsch
+    Assets:Bank EUR                  10 EUR @@ 11 USD
+    Assets:Kasse                    -11 USD
+"#;
+        let file = read_journal_file(journal.as_bytes()).unwrap();
+
+        match import_journal_file(
+            "Buchhaltung".to_string(),
+            &file,
+            &AccountPrefixes::default(),
+            "CHF",
+        ) {
+            Err(AccError::InvalidJournal(errors)) => {
+                assert_eq!(errors.len(), 1);
+                assert_eq!(errors[0].line, 13);
+                assert_eq!(
+                    errors[0].problem,
+                    EntryProblem::Cost("Assets:Bank EUR".to_string())
+                );
+            }
+            other => panic!("unexpected result {other:?}"),
+        }
+        let journal = journal.split("\n2022-01-07").next().unwrap();
+        let file = read_journal_file(journal.as_bytes()).unwrap();
+
+        let ledger = import_journal_file(
+            "Buchhaltung".to_string(),
+            &file,
+            &AccountPrefixes::default(),
+            "CHF",
+        )
+        .unwrap();
+
+        let accounts = from_ledger_definition(&ledger.definition).unwrap();
+        assert_eq!(accounts["Bank EUR"].currency(), Some("EUR"));
+        assert_eq!(ledger.definition.currency().rates.len(), 1);
+        assert_eq!(
+            ledger.transactions[0].read_lines(),
+            vec![
+                PostingLine::debit("Bank EUR", AmountEntity::new(100, 0)).with_currency("EUR"),
+                PostingLine::credit("Kasse", AmountEntity::new(98, 0)),
+            ]
+        );
+        assert_eq!(
+            ledger.transactions[1].read_lines(),
+            vec![
+                PostingLine::debit("Material", AmountEntity::new(49, 0)),
+                PostingLine::credit("Kasse", AmountEntity::new(49, 0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_import_journal_file_reports_unrepresentable_entries() {
+        let journal = r#"
+2022-01-02 Wechsel
+    Assets:Bank EUR               100 EUR
+    Assets:Kasse                 -98 CHF
+
+2022-01-03 Unbekannt
+    Assets:Kasse                   10 CHF
+    Sonstiges:Ertrag
+
+2022-01-04 Ungleich
+    Assets:Kasse:Hauptkasse        10 CHF
+    Income:Ertrag                  -9 CHF
+
+2022-01-05 Kasse
+    Assets:Kasse                   10 CHF
+    Income:Ertrag
+"#;
+        let file = read_journal_file(journal.as_bytes()).unwrap();
+
+        match import_journal_file(
+            "Buchhaltung".to_string(),
+            &file,
+            &AccountPrefixes::default(),
+            "CHF",
+        ) {
+            Err(AccError::InvalidJournal(errors)) => {
+                let problems: Vec<(usize, EntryProblem)> = errors
+                    .into_iter()
+                    .map(|error| (error.line, error.problem))
+                    .collect();
+                assert_eq!(
+                    problems,
+                    vec![
+                        (
+                            2,
+                            EntryProblem::MixedCurrencies(vec![
+                                "CHF".to_string(),
+                                "EUR".to_string()
+                            ])
+                        ),
+                        (
+                            6,
+                            EntryProblem::UnknownPrefix("Sonstiges:Ertrag".to_string())
+                        ),
+                        (
+                            10,
+                            EntryProblem::Unbalanced {
+                                difference: AmountEntity::new(1, 0),
+                                currency: "CHF".to_string()
+                            }
+                        ),
+                        (14, EntryProblem::ParentAccount("Assets:Kasse".to_string())),
+                    ]
+                );
+            }
+            other => panic!("unexpected result {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,376 @@
+use super::account::LedgerAccount;
+use super::account::{find_account, has_sub_accounts, AccountType, ActiveType, BalanceType};
+use super::currency::Currencies;
+use crate::model::entity::{
+    AmountEntity, BankStatement, PostingSide, StatementEntry, TransactionAccountReader,
+};
+use crate::model::error::{AccError, Result};
+use chrono::NaiveDate;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+const REPORT_WIDTH: usize = 66;
+
+/// Movement on the reconciled account, `index` is the position of the transaction in the ledger
+/// and `None` for entries of the statement.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ReconciliationItem {
+    index: Option<usize>,
+    date: NaiveDate,
+    description: String,
+    amount: AmountEntity,
+}
+
+impl ReconciliationItem {
+    fn from(entry: &StatementEntry) -> Self {
+        ReconciliationItem {
+            index: None,
+            date: entry.date,
+            description: entry.description.clone(),
+            amount: entry.amount,
+        }
+    }
+
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    pub fn date(&self) -> NaiveDate {
+        self.date
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// Increase of the account, negative for a decrease.
+    pub fn amount(&self) -> AmountEntity {
+        self.amount
+    }
+}
+
+/// Comparison of a working capital account with a bank statement.
+/// Statement entries and ledger transactions match if they have the same date and amount.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Reconciliation {
+    account: String,
+    until: NaiveDate,
+    matched: Vec<ReconciliationItem>,
+    unmatched_statement: Vec<ReconciliationItem>,
+    unmatched_ledger: Vec<ReconciliationItem>,
+    statement_balance: AmountEntity,
+    ledger_balance: AmountEntity,
+    difference: AmountEntity,
+}
+
+impl Reconciliation {
+    /// Compares the ledger transactions dated within the statement with its entries.
+    /// Only the movements on the account are needed, so counter-accounts may still be placeholders.
+    /// Amounts of foreign currency accounts are compared in their own currency.
+    /// A parent account cannot be reconciled, a statement belongs to one of its sub-accounts.
+    pub fn from(
+        transactions: &[Box<dyn TransactionAccountReader>],
+        accounts: &HashMap<String, LedgerAccount>,
+        currencies: &Currencies,
+        reference: &str,
+        statement: &BankStatement,
+    ) -> Result<Self> {
+        let account = find_account(accounts, reference)
+            .ok_or_else(|| AccError::UnknownAccount(reference.to_string()))?;
+        if account.account_type()
+            != &AccountType::Balance(BalanceType::Active(ActiveType::WorkingCapital))
+            || has_sub_accounts(accounts, account.name())
+        {
+            return Err(AccError::InvalidReconciliationAccount(
+                account.name().to_string(),
+            ));
+        }
+        let closing = statement.closing().ok_or_else(|| AccError::Import {
+            line: None,
+            message: "statement without balance".to_string(),
+        })?;
+        let from = statement
+            .entries
+            .iter()
+            .map(|entry| entry.date)
+            .chain(statement.balances.iter().map(|balance| balance.date))
+            .min()
+            .unwrap_or(closing.date);
+        let foreign = !currencies.is_base(account.currency());
+        let mut ledger_balance = account.start();
+        let mut unmatched_ledger = vec![];
+        for (index, transaction) in transactions.iter().enumerate() {
+            let date = transaction.read_date();
+            if date > closing.date {
+                continue;
+            }
+            let lines: Vec<_> = transaction
+                .read_lines()
+                .into_iter()
+                .filter(|line| {
+                    find_account(accounts, &line.account)
+                        .is_some_and(|line_account| line_account.name() == account.name())
+                })
+                .collect();
+            if lines.is_empty() {
+                continue;
+            }
+            let mut amount = AmountEntity::zero();
+            for line in lines.iter() {
+                let line_amount = if foreign {
+                    line.amount
+                } else {
+                    currencies.to_base(line.amount, line.currency.as_deref(), date)?
+                };
+                amount = match line.side {
+                    PostingSide::Debit => amount.checked_add(line_amount)?,
+                    PostingSide::Credit => amount.checked_sub(line_amount)?,
+                };
+            }
+            ledger_balance = ledger_balance.checked_add(amount)?;
+            if date >= from {
+                unmatched_ledger.push(ReconciliationItem {
+                    index: Some(index),
+                    date,
+                    description: transaction.read_description().to_string(),
+                    amount,
+                });
+            }
+        }
+        unmatched_ledger.sort_by_key(|item| item.date);
+        let mut matched = vec![];
+        let mut unmatched_statement = vec![];
+        for entry in statement.entries.iter() {
+            match unmatched_ledger
+                .iter()
+                .position(|item| item.date == entry.date && item.amount == entry.amount)
+            {
+                Some(position) => matched.push(unmatched_ledger.remove(position)),
+                None => unmatched_statement.push(ReconciliationItem::from(entry)),
+            }
+        }
+        Ok(Reconciliation {
+            account: account.label(),
+            until: closing.date,
+            matched,
+            unmatched_statement,
+            unmatched_ledger,
+            statement_balance: closing.amount,
+            ledger_balance,
+            difference: closing.amount.checked_sub(ledger_balance)?,
+        })
+    }
+
+    /// Ledger transactions matched with a statement entry.
+    pub fn matched(&self) -> &[ReconciliationItem] {
+        &self.matched
+    }
+
+    pub fn unmatched_statement(&self) -> &[ReconciliationItem] {
+        &self.unmatched_statement
+    }
+
+    pub fn unmatched_ledger(&self) -> &[ReconciliationItem] {
+        &self.unmatched_ledger
+    }
+
+    pub fn statement_balance(&self) -> AmountEntity {
+        self.statement_balance
+    }
+
+    pub fn ledger_balance(&self) -> AmountEntity {
+        self.ledger_balance
+    }
+
+    /// Statement closing balance less the balance of the account at the same date.
+    pub fn difference(&self) -> AmountEntity {
+        self.difference
+    }
+
+    pub fn is_reconciled(&self) -> bool {
+        self.difference().is_zero()
+            && self.unmatched_statement.is_empty()
+            && self.unmatched_ledger.is_empty()
+    }
+}
+
+fn write_items(
+    f: &mut Formatter<'_>,
+    title: &str,
+    items: &[ReconciliationItem],
+) -> std::fmt::Result {
+    if items.is_empty() {
+        return Ok(());
+    }
+    writeln!(f, "{title}")?;
+    for item in items {
+        writeln!(
+            f,
+            "    {:<12}{:<34}{:>16}",
+            item.date.to_string(),
+            item.description,
+            item.amount
+        )?;
+    }
+    Ok(())
+}
+
+impl Display for Reconciliation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Reconciliation {} until {}", self.account, self.until)?;
+        writeln!(f, "{}", "=".repeat(REPORT_WIDTH))?;
+        writeln!(
+            f,
+            "{:<50}{:>16}",
+            "Matched Transactions",
+            self.matched.len()
+        )?;
+        write_items(f, "Unmatched Statement Entries", &self.unmatched_statement)?;
+        write_items(f, "Unmatched Ledger Transactions", &self.unmatched_ledger)?;
+        writeln!(f, "{}", "-".repeat(REPORT_WIDTH))?;
+        writeln!(
+            f,
+            "{:<50}{:>16}",
+            "Statement Balance", self.statement_balance
+        )?;
+        writeln!(f, "{:<50}{:>16}", "Ledger Balance", self.ledger_balance)?;
+        writeln!(f, "{:<50}{:>16}", "Difference", self.difference())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Reconciliation;
+    use crate::model::core::account::{AccountType, ActiveType, BalanceType, LedgerAccount};
+    use crate::model::core::currency::Currencies;
+    use crate::model::core::test_support::{
+        date, ledger_accounts, mock_compound_transaction, mock_transaction,
+    };
+    use crate::model::entity::{
+        AmountEntity, BankStatement, PostingLine, StatementBalance, StatementEntry,
+    };
+    use crate::model::error::{AccError, Result};
+
+    fn entry(day: u32, amount: i64) -> StatementEntry {
+        StatementEntry {
+            date: date(3, day),
+            amount: AmountEntity::new(amount, 0),
+            description: "Eintrag".to_string(),
+            ..StatementEntry::default()
+        }
+    }
+
+    #[test]
+    fn test_reconciliation() -> Result<()> {
+        let transactions = vec![
+            mock_transaction("Bank", "Ertrag", AmountEntity::new(50, 0), date(3, 2)),
+            mock_transaction("Aufwand", "Bank", AmountEntity::new(20, 0), date(3, 5)),
+            mock_transaction("Aufwand", "Bank", AmountEntity::new(10, 0), date(3, 7)),
+            mock_transaction("Bank", "Ertrag", AmountEntity::new(99, 0), date(3, 30)),
+        ];
+        let statement = BankStatement {
+            balances: vec![StatementBalance {
+                date: date(3, 10),
+                amount: AmountEntity::new(125, 0),
+            }],
+            entries: vec![entry(2, 50), entry(5, -20), entry(8, -5)],
+        };
+
+        let reconciliation = Reconciliation::from(
+            &transactions,
+            &ledger_accounts([]),
+            &Currencies::default(),
+            "Bank",
+            &statement,
+        )?;
+
+        let matched: Vec<Option<usize>> = reconciliation
+            .matched()
+            .iter()
+            .map(|item| item.index())
+            .collect();
+        assert_eq!(matched, vec![Some(0), Some(1)]);
+        assert_eq!(reconciliation.unmatched_statement()[0].date(), date(3, 8));
+        assert_eq!(reconciliation.unmatched_ledger().len(), 1);
+        assert_eq!(
+            reconciliation.unmatched_ledger()[0].amount(),
+            AmountEntity::new(-10, 0)
+        );
+        assert_eq!(reconciliation.ledger_balance(), AmountEntity::new(120, 0));
+        assert_eq!(reconciliation.difference(), AmountEntity::new(5, 0));
+        assert!(!reconciliation.is_reconciled());
+        Ok(())
+    }
+
+    #[test]
+    fn test_reconcile_income_account() {
+        let statement = BankStatement {
+            balances: vec![StatementBalance {
+                date: date(3, 10),
+                amount: AmountEntity::zero(),
+            }],
+            entries: vec![],
+        };
+
+        assert!(matches!(
+            Reconciliation::from(
+                &[],
+                &ledger_accounts([]),
+                &Currencies::default(),
+                "Ertrag",
+                &statement
+            ),
+            Err(AccError::InvalidReconciliationAccount(_))
+        ));
+    }
+
+    #[test]
+    fn test_reconcile_parent_account() {
+        let bank_type = AccountType::Balance(BalanceType::Active(ActiveType::WorkingCapital));
+        let accounts =
+            ledger_accounts([
+                LedgerAccount::new("Bank:UBS", AmountEntity::zero(), bank_type).with_parent("Bank"),
+            ]);
+        let statement = BankStatement {
+            balances: vec![StatementBalance {
+                date: date(3, 10),
+                amount: AmountEntity::zero(),
+            }],
+            entries: vec![],
+        };
+
+        assert!(matches!(
+            Reconciliation::from(&[], &accounts, &Currencies::default(), "Bank", &statement),
+            Err(AccError::InvalidReconciliationAccount(_))
+        ));
+    }
+
+    #[test]
+    fn test_reconcile_without_rate() {
+        let transactions = vec![mock_compound_transaction(
+            vec![
+                PostingLine::debit("Bank", AmountEntity::new(50, 0)).with_currency("EUR"),
+                PostingLine::credit("Ertrag", AmountEntity::new(50, 0)).with_currency("EUR"),
+            ],
+            date(3, 2),
+        )];
+        let statement = BankStatement {
+            balances: vec![StatementBalance {
+                date: date(3, 10),
+                amount: AmountEntity::new(150, 0),
+            }],
+            entries: vec![entry(2, 50)],
+        };
+
+        assert!(matches!(
+            Reconciliation::from(
+                &transactions,
+                &ledger_accounts([]),
+                &Currencies::default(),
+                "Bank",
+                &statement
+            ),
+            Err(AccError::MissingRate(_))
+        ));
+    }
+}
@@ -0,0 +1,176 @@
+use super::account::{find_account, has_sub_accounts, LedgerAccount};
+use crate::model::entity::{AmountEntity, RuleEntity, StatementEntry, TaxCode};
+use crate::model::error::{AccError, Result};
+use regex::Regex;
+use std::collections::HashMap;
+
+#[derive(Clone, Debug)]
+struct Rule {
+    description: Option<Regex>,
+    min_amount: Option<AmountEntity>,
+    max_amount: Option<AmountEntity>,
+    counterparty: Option<String>,
+    account: String,
+    tax: Option<TaxCode>,
+}
+
+impl Rule {
+    fn matches(&self, entry: &StatementEntry) -> bool {
+        let amount = entry.amount.abs();
+        self.description
+            .as_ref()
+            .is_none_or(|description| description.is_match(&entry.description))
+            && self
+                .min_amount
+                .is_none_or(|min_amount| amount >= min_amount)
+            && self
+                .max_amount
+                .is_none_or(|max_amount| amount <= max_amount)
+            && self.counterparty.as_ref().is_none_or(|counterparty| {
+                entry
+                    .counterparty
+                    .as_ref()
+                    .is_some_and(|name| name.to_lowercase() == counterparty.to_lowercase())
+            })
+    }
+}
+
+/// Rules of the ledger assigning the counter-account of imported bank entries.
+#[derive(Clone, Debug, Default)]
+pub struct Rules {
+    rules: Vec<Rule>,
+}
+
+impl Rules {
+    /// Compiles the rules, every rule has to book onto a defined account without sub-accounts.
+    pub fn from(
+        definitions: &[RuleEntity],
+        accounts: &HashMap<String, LedgerAccount>,
+    ) -> Result<Self> {
+        let rules = definitions
+            .iter()
+            .enumerate()
+            .map(|(index, definition)| {
+                let invalid = |message: String| AccError::InvalidRule {
+                    number: index + 1,
+                    message,
+                };
+                let account = find_account(accounts, &definition.account).ok_or_else(|| {
+                    invalid(format!("account {} is not defined", definition.account))
+                })?;
+                if has_sub_accounts(accounts, account.name()) {
+                    return Err(invalid(format!(
+                        "account {} has sub-accounts",
+                        account.name()
+                    )));
+                }
+                let description = definition
+                    .description
+                    .as_deref()
+                    .map(Regex::new)
+                    .transpose()
+                    .map_err(|err| invalid(err.to_string()))?;
+                Ok(Rule {
+                    description,
+                    min_amount: definition.min_amount,
+                    max_amount: definition.max_amount,
+                    counterparty: definition.counterparty.clone(),
+                    account: account.name().to_string(),
+                    tax: definition.tax,
+                })
+            })
+            .collect::<Result<Vec<Rule>>>()?;
+        Ok(Rules { rules })
+    }
+
+    /// Counter-account and tax code of the first rule matching the entry.
+    pub fn assign(&self, entry: &StatementEntry) -> Option<(&str, Option<TaxCode>)> {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(entry))
+            .map(|rule| (rule.account.as_str(), rule.tax))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rules;
+    use crate::model::core::test_support::ledger_accounts;
+    use crate::model::entity::{AmountEntity, RuleEntity, StatementEntry, TaxCode};
+    use crate::model::error::{AccError, Result};
+
+    fn rule(account: &str) -> RuleEntity {
+        RuleEntity {
+            description: None,
+            min_amount: None,
+            max_amount: None,
+            counterparty: None,
+            account: account.to_string(),
+            tax: None,
+        }
+    }
+
+    fn entry(description: &str, amount: i64, counterparty: Option<&str>) -> StatementEntry {
+        StatementEntry {
+            amount: AmountEntity::new(amount, 0),
+            description: description.to_string(),
+            counterparty: counterparty.map(str::to_string),
+            ..StatementEntry::default()
+        }
+    }
+
+    #[test]
+    fn test_first_matching_rule_assigns() -> Result<()> {
+        let rules = Rules::from(
+            &[
+                RuleEntity {
+                    description: Some("(?i)migros|coop".to_string()),
+                    max_amount: Some(AmountEntity::new(100, 0)),
+                    tax: Some(TaxCode::Reduced),
+                    ..rule("6000")
+                },
+                RuleEntity {
+                    counterparty: Some("muster ag".to_string()),
+                    ..rule("Ertrag")
+                },
+            ],
+            &ledger_accounts([]),
+        )?;
+
+        assert_eq!(
+            rules.assign(&entry("Einkauf MIGROS Bern", -80, None)),
+            Some(("Aufwand", Some(TaxCode::Reduced)))
+        );
+        assert_eq!(
+            rules.assign(&entry("Einkauf Migros Bern", -180, None)),
+            None
+        );
+        assert_eq!(
+            rules.assign(&entry("Rechnung 42", 1500, Some("Muster AG"))),
+            Some(("Ertrag", None))
+        );
+        assert_eq!(rules.assign(&entry("Rechnung 42", 1500, None)), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rule_with_unknown_account() {
+        assert!(matches!(
+            Rules::from(&[rule("Ertrag"), rule("Unbekannt")], &ledger_accounts([])),
+            Err(AccError::InvalidRule { number: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_rule_with_invalid_regex() {
+        let invalid = RuleEntity {
+            description: Some("(".to_string()),
+            ..rule("Ertrag")
+        };
+
+        assert!(matches!(
+            Rules::from(&[invalid], &ledger_accounts([])),
+            Err(AccError::InvalidRule { number: 1, .. })
+        ));
+    }
+}
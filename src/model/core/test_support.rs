@@ -0,0 +1,94 @@
+use super::account::{
+    AccountType, ActiveType, BalanceType, IncomeType, LedgerAccount, PassiveType,
+};
+use crate::model::entity::{
+    AmountEntity, MockTransactionEntity, PostingLine, TransactionAccountReader,
+};
+use chrono::NaiveDate;
+use std::collections::HashMap;
+
+/// Date in 2022, the year the tests book in.
+pub fn date(month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2022, month, day).unwrap()
+}
+
+/// Accounts keyed by their name.
+pub fn accounts(
+    accounts: impl IntoIterator<Item = LedgerAccount>,
+) -> HashMap<String, LedgerAccount> {
+    accounts
+        .into_iter()
+        .map(|account| (account.name().to_string(), account))
+        .collect()
+}
+
+/// Cash, bank, equity, revenue and expense account, replaced or extended by `others`.
+pub fn ledger_accounts(
+    others: impl IntoIterator<Item = LedgerAccount>,
+) -> HashMap<String, LedgerAccount> {
+    let working_capital = AccountType::Balance(BalanceType::Active(ActiveType::WorkingCapital));
+    let mut ledger_accounts = accounts([
+        LedgerAccount::new("Kasse", AmountEntity::new(100, 0), working_capital.clone())
+            .with_number(1000),
+        LedgerAccount::new("Bank", AmountEntity::new(100, 0), working_capital).with_number(1020),
+        LedgerAccount::new(
+            "Eigenkapital",
+            AmountEntity::new(200, 0),
+            AccountType::Balance(BalanceType::Passive(PassiveType::Equity)),
+        )
+        .with_number(2800),
+        LedgerAccount::new(
+            "Ertrag",
+            AmountEntity::zero(),
+            AccountType::Income(IncomeType::Revenue),
+        )
+        .with_number(3000),
+        LedgerAccount::new(
+            "Aufwand",
+            AmountEntity::zero(),
+            AccountType::Income(IncomeType::Expense),
+        )
+        .with_number(6000),
+    ]);
+    ledger_accounts.extend(accounts(others));
+    ledger_accounts
+}
+
+/// Transaction with the posting lines, described as `description`.
+pub fn mock_described_transaction(
+    lines: Vec<PostingLine>,
+    description: &str,
+    date: NaiveDate,
+) -> Box<dyn TransactionAccountReader> {
+    let mut mock_transaction = MockTransactionEntity::new();
+    mock_transaction.expect_read_lines().return_const(lines);
+    mock_transaction
+        .expect_read_description()
+        .return_const(description.to_owned());
+    mock_transaction.expect_read_date().return_const(date);
+    Box::new(mock_transaction)
+}
+
+/// Transaction with the posting lines, described as `Buchung`.
+pub fn mock_compound_transaction(
+    lines: Vec<PostingLine>,
+    date: NaiveDate,
+) -> Box<dyn TransactionAccountReader> {
+    mock_described_transaction(lines, "Buchung", date)
+}
+
+/// Transaction booking `amount` from the `debitor` to the `creditor` account.
+pub fn mock_transaction(
+    debitor: &str,
+    creditor: &str,
+    amount: AmountEntity,
+    date: NaiveDate,
+) -> Box<dyn TransactionAccountReader> {
+    mock_compound_transaction(
+        vec![
+            PostingLine::debit(debitor, amount),
+            PostingLine::credit(creditor, amount),
+        ],
+        date,
+    )
+}
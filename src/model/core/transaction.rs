@@ -1,75 +1,1169 @@
-use super::super::core::account::{AccountType, LedgerAccount};
+use super::super::core::account::{find_account, has_sub_accounts, AccountType, LedgerAccount};
 use super::super::error::Result;
-use crate::model::entity::{
-    AmountEntity, LedgerEntity, TransactionAccountReader, TransactionEntity,
-};
-use crate::model::error::AccError;
+use super::currency::{check_currencies, Currencies};
+use super::fiscal_year::FiscalYear;
+use crate::model::entity::{AmountEntity, PostingLine, PostingSide, TransactionAccountReader};
+use crate::model::error::{AccError, TransactionError, TransactionProblem};
+use chrono::NaiveDate;
+use std::cmp::Ordering;
 use std::collections::HashMap;
 
-struct CalculatedAccount {
-    name: String,
-    current_ammount: AmountEntity,
-    account_type: AccountType,
+#[derive(Clone, PartialEq, Debug)]
+pub struct CalculatedAccount {
+    account: LedgerAccount,
+    start_amount: AmountEntity,
+    current_amount: AmountEntity,
+    foreign_amount: Option<AmountEntity>,
 }
 
-struct TransactionResult {
+impl CalculatedAccount {
+    /// Amounts are kept in the base currency, foreign currency accounts additionally keep their foreign balance.
+    fn from(account: &LedgerAccount, currencies: &Currencies) -> Result<Self> {
+        let start_amount = currencies.opening_value(account)?;
+        Ok(CalculatedAccount {
+            account: account.clone(),
+            start_amount,
+            current_amount: start_amount,
+            foreign_amount: (!currencies.is_base(account.currency())).then(|| account.start()),
+        })
+    }
+
+    fn signed(&self, side: PostingSide, amount: AmountEntity) -> AmountEntity {
+        if (side == PostingSide::Debit) == self.account.account_type().increases_on_debit() {
+            amount
+        } else {
+            -amount
+        }
+    }
+
+    /// Books `amount` in base currency and `foreign` in the currency of the account.
+    /// A foreign currency account carries its balance at the average rate it was built up with,
+    /// so when the balance is reduced, the difference between its book value and the matching part
+    /// of `amount` is realized. A part exceeding the balance is booked at the rate of `amount`.
+    /// Returns the realized difference, which has to be booked on the opposite side.
+    fn post(
+        &mut self,
+        side: PostingSide,
+        amount: AmountEntity,
+        foreign: AmountEntity,
+    ) -> Result<AmountEntity> {
+        let signed_foreign = self.signed(side, foreign);
+        match self.foreign_amount {
+            Some(balance)
+                if !balance.is_zero() && signed_foreign.is_negative() != balance.is_negative() =>
+            {
+                let reduced = signed_foreign.abs().min(balance.abs());
+                let book_value = self.current_amount.share(reduced, balance.abs())?;
+                let exceeding = amount.share(
+                    signed_foreign.abs().checked_sub(reduced)?,
+                    signed_foreign.abs(),
+                )?;
+                let realized = amount.checked_sub(exceeding)?;
+                self.foreign_amount = Some(balance.checked_add(signed_foreign)?);
+                self.current_amount = self
+                    .current_amount
+                    .checked_sub(book_value)?
+                    .checked_add(self.signed(side, exceeding))?;
+                (-self.signed(side, book_value)).checked_sub(realized)
+            }
+            Some(balance) => {
+                self.foreign_amount = Some(balance.checked_add(signed_foreign)?);
+                self.current_amount = self.current_amount.checked_add(self.signed(side, amount))?;
+                Ok(AmountEntity::zero())
+            }
+            None => {
+                self.current_amount = self.current_amount.checked_add(self.signed(side, amount))?;
+                Ok(AmountEntity::zero())
+            }
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        self.account.name()
+    }
+
+    pub fn number(&self) -> Option<u32> {
+        self.account.number()
+    }
+
+    pub fn label(&self) -> String {
+        self.account.label()
+    }
+
+    pub fn parent(&self) -> Option<&str> {
+        self.account.parent()
+    }
+
+    /// Opening balance in base currency.
+    pub fn start_amount(&self) -> AmountEntity {
+        self.start_amount
+    }
+
+    /// Balance in base currency.
+    pub fn current_amount(&self) -> AmountEntity {
+        self.current_amount
+    }
+
+    /// Currency the account is kept in, `None` for the base currency.
+    pub fn currency(&self) -> Option<&str> {
+        self.account.currency()
+    }
+
+    /// Balance in the currency of the account, `None` for base currency accounts.
+    pub fn foreign_amount(&self) -> Option<AmountEntity> {
+        self.foreign_amount
+    }
+
+    /// Change of the account caused by the booked transactions, without the start amount.
+    pub fn movement(&self) -> Result<AmountEntity> {
+        self.current_amount.checked_sub(self.start_amount)
+    }
+
+    pub fn account_type(&self) -> &AccountType {
+        self.account.account_type()
+    }
+
+    /// Orders accounts by number, accounts without a number follow sorted by name.
+    pub fn cmp_by_number(&self, other: &Self) -> Ordering {
+        self.account.cmp_by_number(&other.account)
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct TransactionResult {
     accounts: HashMap<String, CalculatedAccount>,
-    ledger_definition: LedgerEntity,
+}
+
+impl TransactionResult {
+    /// Looks up an account by its name or number.
+    pub fn get_account(&self, reference: &str) -> Option<&CalculatedAccount> {
+        self.accounts.get(reference).or_else(|| {
+            let number: u32 = reference.trim().parse().ok()?;
+            self.accounts
+                .values()
+                .find(|account| account.number() == Some(number))
+        })
+    }
+
+    pub fn accounts(&self) -> impl Iterator<Item = &CalculatedAccount> {
+        self.accounts.values()
+    }
+
+    /// Direct sub-accounts of the account, sorted by number.
+    pub fn sub_accounts(&self, name: &str) -> Vec<&CalculatedAccount> {
+        let mut sub_accounts: Vec<&CalculatedAccount> = self
+            .accounts()
+            .filter(|account| account.parent() == Some(name))
+            .collect();
+        sub_accounts.sort_by(|a, b| a.cmp_by_number(b));
+        sub_accounts
+    }
+
+    /// Accounts of the type as tree with their depth, every parent is followed by its sub-accounts.
+    pub fn account_tree(&self, account_type: &AccountType) -> Vec<(usize, &CalculatedAccount)> {
+        let mut roots: Vec<&CalculatedAccount> = self
+            .accounts()
+            .filter(|account| account.account_type() == account_type && account.parent().is_none())
+            .collect();
+        roots.sort_by(|a, b| a.cmp_by_number(b));
+        let mut tree = vec![];
+        for root in roots {
+            self.collect_tree(root, 0, &mut tree);
+        }
+        tree
+    }
+
+    fn collect_tree<'a>(
+        &'a self,
+        account: &'a CalculatedAccount,
+        depth: usize,
+        tree: &mut Vec<(usize, &'a CalculatedAccount)>,
+    ) {
+        tree.push((depth, account));
+        for sub_account in self.sub_accounts(account.name()) {
+            self.collect_tree(sub_account, depth + 1, tree);
+        }
+    }
+
+    /// Sums `amount` over the account and all of its sub-accounts.
+    pub fn rolled_up(
+        &self,
+        account: &CalculatedAccount,
+        amount: &dyn Fn(&CalculatedAccount) -> Result<AmountEntity>,
+    ) -> Result<AmountEntity> {
+        let mut total = amount(account)?;
+        for sub_account in self.sub_accounts(account.name()) {
+            total = total.checked_add(self.rolled_up(sub_account, amount)?)?;
+        }
+        Ok(total)
+    }
 }
 
 pub fn check_transactions(
-    transactions: &Vec<Box<dyn TransactionAccountReader>>,
+    transactions: &[Box<dyn TransactionAccountReader>],
     accounts: &HashMap<String, LedgerAccount>,
+    currencies: &Currencies,
 ) -> Result<()> {
-    for transaction in transactions.iter() {
-        if !accounts.contains_key(transaction.read_creditor_account()) {
-            return Err(AccError::new(format!(
-                "creditor Account {} from Transaction {} not found in defined accounts",
-                transaction.read_creditor_account().to_owned(),
-                transaction.read_description().to_owned()
-            )));
+    check_currencies(accounts, currencies)?;
+    let errors = validate_transactions(transactions, accounts, currencies, None);
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(AccError::InvalidTransactions(errors))
+    }
+}
+
+/// Checks transactions about to be added to the ledger. Besides the checks of [validate_transactions]
+/// they must not be dated within a locked period of the fiscal year.
+pub fn check_new_transactions(
+    transactions: &[Box<dyn TransactionAccountReader>],
+    accounts: &HashMap<String, LedgerAccount>,
+    currencies: &Currencies,
+    fiscal_year: Option<&FiscalYear>,
+) -> Result<()> {
+    check_currencies(accounts, currencies)?;
+    let mut errors = validate_transactions(transactions, accounts, currencies, fiscal_year);
+    if let Some(fiscal_year) = fiscal_year {
+        for (index, transaction) in transactions.iter().enumerate() {
+            let date = transaction.read_date();
+            if let Some(period) = fiscal_year.period(date).filter(|period| period.is_locked()) {
+                errors.push(TransactionError {
+                    index,
+                    description: transaction.read_description().to_string(),
+                    problem: TransactionProblem::LockedPeriod {
+                        number: period.number(),
+                        date,
+                    },
+                });
+            }
         }
-        if !accounts.contains_key(transaction.read_debitor_account()) {
-            return Err(AccError::new(format!(
-                "debitor Account {} from Transaction {} not found in defined accounts",
-                transaction.read_debitor_account().to_owned(),
-                transaction.read_description().to_owned()
-            )));
+    }
+    errors.sort_by_key(|error| error.index);
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(AccError::InvalidTransactions(errors))
+    }
+}
+
+/// Collects every problem of every transaction instead of stopping at the first one.
+/// Dates are only checked when a fiscal year is given.
+/// Debit and credit totals are compared in base currency, converted like [book_transactions] converts them.
+pub fn validate_transactions(
+    transactions: &[Box<dyn TransactionAccountReader>],
+    accounts: &HashMap<String, LedgerAccount>,
+    currencies: &Currencies,
+    fiscal_year: Option<&FiscalYear>,
+) -> Vec<TransactionError> {
+    let mut errors = vec![];
+    for (index, transaction) in transactions.iter().enumerate() {
+        let mut report = |problem: TransactionProblem| {
+            errors.push(TransactionError {
+                index,
+                description: transaction.read_description().to_string(),
+                problem,
+            })
+        };
+        let date = transaction.read_date();
+        let lines = transaction.read_lines();
+        let mut debited = vec![];
+        let mut credited = vec![];
+        for line in lines.iter() {
+            match find_account(accounts, &line.account) {
+                Some(account) => {
+                    if has_sub_accounts(accounts, account.name()) {
+                        report(TransactionProblem::ParentAccount(
+                            account.name().to_string(),
+                        ));
+                    } else if line.side == PostingSide::Debit {
+                        debited.push(account.name());
+                    } else {
+                        credited.push(account.name());
+                    }
+                    if !currencies.can_book(account.currency(), line.currency.as_deref()) {
+                        report(TransactionProblem::CurrencyMismatch {
+                            account: account.name().to_string(),
+                            currency: currencies.code(line.currency.as_deref()).to_string(),
+                        });
+                    }
+                }
+                None => report(TransactionProblem::UnknownAccount(line.account.clone())),
+            }
+        }
+        if let Some(account) = debited.iter().find(|account| credited.contains(account)) {
+            report(TransactionProblem::SameAccount(account.to_string()));
+        }
+        if lines.is_empty() || lines.iter().any(|line| line.amount.is_zero()) {
+            report(TransactionProblem::ZeroAmount);
+        }
+        let totals = base_amounts(&lines, currencies, date).and_then(|amounts| {
+            let side_total = |side: PostingSide| {
+                AmountEntity::checked_sum(
+                    lines
+                        .iter()
+                        .zip(amounts.iter())
+                        .filter(|(line, _)| line.side == side)
+                        .map(|(_, amount)| *amount),
+                )
+            };
+            Ok((
+                side_total(PostingSide::Debit)?,
+                side_total(PostingSide::Credit)?,
+            ))
+        });
+        match totals {
+            Ok((debit, credit)) if debit != credit => {
+                report(TransactionProblem::Unbalanced { debit, credit })
+            }
+            Ok(_) => {}
+            Err(AccError::MissingRate(currency)) => {
+                report(TransactionProblem::MissingRate { currency, date })
+            }
+            Err(_) => report(TransactionProblem::AmountOverflow),
+        }
+        if let Some(fiscal_year) = fiscal_year {
+            if !fiscal_year.contains(date) {
+                report(TransactionProblem::OutsideFiscalYear(date));
+            }
+        }
+    }
+    errors
+}
+
+/// Amounts of the posting lines in base currency. The lines of each currency and side are converted by
+/// their running total, so the rounding difference lands on one line and a transaction balanced in its
+/// own currency stays balanced in base currency.
+fn base_amounts(
+    lines: &[PostingLine],
+    currencies: &Currencies,
+    date: NaiveDate,
+) -> Result<Vec<AmountEntity>> {
+    let mut totals: HashMap<(&str, PostingSide), (AmountEntity, AmountEntity)> = HashMap::new();
+    lines
+        .iter()
+        .map(|line| {
+            let currency = line.currency.as_deref();
+            let (total, converted) = totals
+                .entry((currencies.code(currency), line.side))
+                .or_default();
+            *total = total.checked_add(line.amount)?;
+            let converted_total = currencies.to_base(*total, currency, date)?;
+            let amount = converted_total.checked_sub(*converted)?;
+            *converted = converted_total;
+            Ok(amount)
+        })
+        .collect()
+}
+
+/// Starts every account at its start amount and books all transactions onto it.
+pub fn calculate_transactions(
+    transactions: &[Box<dyn TransactionAccountReader>],
+    accounts: &HashMap<String, LedgerAccount>,
+    currencies: &Currencies,
+) -> Result<TransactionResult> {
+    calculate_filtered_transactions(transactions, accounts, currencies, |_| true)
+}
+
+/// Like [calculate_transactions] but only books transactions dated on or before `until`.
+pub fn calculate_transactions_until(
+    transactions: &[Box<dyn TransactionAccountReader>],
+    accounts: &HashMap<String, LedgerAccount>,
+    currencies: &Currencies,
+    until: NaiveDate,
+) -> Result<TransactionResult> {
+    calculate_filtered_transactions(transactions, accounts, currencies, |date| date <= until)
+}
+
+/// Like [calculate_transactions] but every account only moves by the bookings dated between `from` and `to`
+/// inclusive. The transactions before `from` are booked as well, so exchange differences realized within
+/// the period are based on the book value of the foreign currency accounts at that time.
+pub fn calculate_transactions_between(
+    transactions: &[Box<dyn TransactionAccountReader>],
+    accounts: &HashMap<String, LedgerAccount>,
+    currencies: &Currencies,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<TransactionResult> {
+    let mut bookings: Vec<Booking> = vec![];
+    book_transactions(
+        transactions,
+        accounts,
+        currencies,
+        |date| date <= to,
+        |booking| {
+            if booking.date >= from {
+                bookings.push(booking)
+            }
+        },
+    )?;
+    let mut period_accounts = accounts
+        .iter()
+        .map(|(name, account)| Ok((name.clone(), CalculatedAccount::from(account, currencies)?)))
+        .collect::<Result<HashMap<String, CalculatedAccount>>>()?;
+    for booking in bookings {
+        let Some(account) = period_accounts.get_mut(&booking.account) else {
+            continue;
+        };
+        account.current_amount = account
+            .current_amount
+            .checked_add(account.signed(booking.side, booking.amount))?;
+        if let (Some(balance), Some(line)) = (account.foreign_amount, booking.line) {
+            let foreign = transactions[booking.transaction].read_lines()[line].amount;
+            account.foreign_amount =
+                Some(balance.checked_add(account.signed(booking.side, foreign))?);
         }
     }
-    Ok(())
+    Ok(TransactionResult {
+        accounts: period_accounts,
+    })
+}
+
+/// Amount an account moved by in base currency, as reported to the observer of [book_transactions].
+/// Realized exchange differences are bookings of their own with the foreign currency account as counter-account.
+pub struct Booking {
+    /// Position of the transaction in the booked transactions.
+    pub transaction: usize,
+    /// Position of the posting line within the transaction, `None` for a realized exchange difference.
+    pub line: Option<usize>,
+    pub date: NaiveDate,
+    pub description: String,
+    pub account: String,
+    /// Account on the opposite side, `None` if there is more than one.
+    pub counter_account: Option<String>,
+    pub side: PostingSide,
+    pub amount: AmountEntity,
+}
+
+fn calculate_filtered_transactions(
+    transactions: &[Box<dyn TransactionAccountReader>],
+    accounts: &HashMap<String, LedgerAccount>,
+    currencies: &Currencies,
+    include: impl Fn(NaiveDate) -> bool,
+) -> Result<TransactionResult> {
+    book_transactions(transactions, accounts, currencies, include, |_| {})
+}
+
+/// Books the included transactions in date order, which matters for realized exchange differences.
+/// Every amount booked onto an account is passed to `observe`.
+pub fn book_transactions(
+    transactions: &[Box<dyn TransactionAccountReader>],
+    accounts: &HashMap<String, LedgerAccount>,
+    currencies: &Currencies,
+    include: impl Fn(NaiveDate) -> bool,
+    mut observe: impl FnMut(Booking),
+) -> Result<TransactionResult> {
+    check_transactions(transactions, accounts, currencies)?;
+    let mut calculated_accounts = accounts
+        .iter()
+        .map(|(name, account)| Ok((name.clone(), CalculatedAccount::from(account, currencies)?)))
+        .collect::<Result<HashMap<String, CalculatedAccount>>>()?;
+    let fx_account = currencies
+        .fx_account()
+        .and_then(|fx_account| find_account(accounts, fx_account))
+        .map(|fx_account| fx_account.name().to_string());
+    let mut included: Vec<(usize, &Box<dyn TransactionAccountReader>)> = transactions
+        .iter()
+        .enumerate()
+        .filter(|(_, transaction)| include(transaction.read_date()))
+        .collect();
+    included.sort_by_key(|(_, transaction)| transaction.read_date());
+    for (index, transaction) in included {
+        let date = transaction.read_date();
+        let lines = transaction.read_lines();
+        let amounts = base_amounts(&lines, currencies, date)?;
+        for (line_index, (line, amount)) in lines.iter().zip(amounts).enumerate() {
+            let Some(account) = find_account(accounts, &line.account) else {
+                continue;
+            };
+            let difference = match calculated_accounts.get_mut(account.name()) {
+                Some(calculated) => calculated.post(line.side, amount, line.amount)?,
+                None => continue,
+            };
+            let mut counter_accounts =
+                lines
+                    .iter()
+                    .filter(|other| other.side != line.side)
+                    .map(|other| {
+                        find_account(accounts, &other.account)
+                            .map(|other| other.name().to_string())
+                            .unwrap_or_else(|| other.account.clone())
+                    });
+            let counter_account = match (counter_accounts.next(), counter_accounts.next()) {
+                (Some(counter_account), None) => Some(counter_account),
+                _ => None,
+            };
+            observe(Booking {
+                transaction: index,
+                line: Some(line_index),
+                date,
+                description: transaction.read_description().to_string(),
+                account: account.name().to_string(),
+                counter_account,
+                side: line.side,
+                amount: amount.checked_add(difference)?,
+            });
+            if let Some(fx_account) = fx_account
+                .as_ref()
+                .and_then(|fx_account| calculated_accounts.get_mut(fx_account))
+                .filter(|_| !difference.is_zero())
+            {
+                fx_account.post(line.side.opposite(), difference, difference)?;
+                observe(Booking {
+                    transaction: index,
+                    line: None,
+                    date,
+                    description: transaction.read_description().to_string(),
+                    account: fx_account.name().to_string(),
+                    counter_account: Some(account.name().to_string()),
+                    side: line.side.opposite(),
+                    amount: difference,
+                });
+            }
+        }
+    }
+    Ok(TransactionResult {
+        accounts: calculated_accounts,
+    })
 }
 
 #[cfg(test)]
 mod tests {
-    use super::super::super::entity::MockTransactionEntity;
-    use super::super::super::error::Result;
-    use crate::model::core::account::LedgerAccount;
+    use super::super::super::entity::{
+        AmountEntity, CurrencyEntity, FiscalYearEntity, MockTransactionEntity, RateEntity,
+    };
+    use super::super::super::error::{AccError, Result, TransactionProblem};
+    use crate::model::core::account::{
+        AccountType, ActiveType, BalanceType, IncomeType, LedgerAccount, PassiveType,
+    };
+    use crate::model::core::currency::Currencies;
+    use crate::model::core::fiscal_year::FiscalYear;
+    use crate::model::core::test_support::{
+        accounts, date, mock_compound_transaction, mock_transaction,
+    };
+    use crate::model::entity::{PostingLine, PostingSide};
     use std::collections::HashMap;
     #[test]
     fn test_check_transactions() -> Result<()> {
         let creditor_account = "Cred";
         let debitor_account = "Deb";
         let mut mock_transaction = MockTransactionEntity::new();
-        let dummy_ledger_account = LedgerAccount::dummy();
+        let m = accounts([
+            LedgerAccount::new(
+                "Cred",
+                AmountEntity::zero(),
+                AccountType::Income(IncomeType::Revenue),
+            ),
+            LedgerAccount::new(
+                "Deb",
+                AmountEntity::zero(),
+                AccountType::Income(IncomeType::Expense),
+            ),
+        ]);
+
+        mock_transaction.expect_read_lines().return_const(vec![
+            PostingLine::debit(debitor_account, AmountEntity::new(1, 0)),
+            PostingLine::credit(creditor_account, AmountEntity::new(1, 0)),
+        ]);
+        mock_transaction.expect_read_date().return_const(date(1, 1));
+
+        assert!(super::check_transactions(
+            &[Box::new(mock_transaction)],
+            &m,
+            &Currencies::default()
+        )
+        .is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_transactions_collects_all_problems() {
+        let accounts = accounts([LedgerAccount::new(
+            "Kasse",
+            AmountEntity::zero(),
+            AccountType::Balance(BalanceType::Active(ActiveType::WorkingCapital)),
+        )]);
+        let transactions = vec![
+            mock_transaction("Kasse", "Unbekannt", AmountEntity::new(1, 0), date(1, 1)),
+            mock_transaction("Kasse", "Kasse", AmountEntity::zero(), date(2, 1)),
+            mock_transaction("Fehlt", "Kasse", AmountEntity::new(1, 0), date(12, 1)),
+        ];
+        let fiscal_year = FiscalYear::from(&FiscalYearEntity {
+            start: date(1, 1),
+            end: date(6, 30),
+            monthly: false,
+            locked: vec![],
+        })
+        .unwrap();
+
+        let errors = super::validate_transactions(
+            &transactions,
+            &accounts,
+            &Currencies::default(),
+            Some(&fiscal_year),
+        );
+
+        let problems: Vec<(usize, TransactionProblem)> = errors
+            .into_iter()
+            .map(|error| (error.index, error.problem))
+            .collect();
+        assert_eq!(
+            problems,
+            vec![
+                (
+                    0,
+                    TransactionProblem::UnknownAccount("Unbekannt".to_string())
+                ),
+                (1, TransactionProblem::SameAccount("Kasse".to_string())),
+                (1, TransactionProblem::ZeroAmount),
+                (2, TransactionProblem::UnknownAccount("Fehlt".to_string())),
+                (2, TransactionProblem::OutsideFiscalYear(date(12, 1))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_new_transactions_in_locked_period() {
+        let working_capital = AccountType::Balance(BalanceType::Active(ActiveType::WorkingCapital));
+        let accounts = accounts([
+            LedgerAccount::new("Kasse", AmountEntity::zero(), working_capital.clone()),
+            LedgerAccount::new("Bank", AmountEntity::zero(), working_capital),
+        ]);
+        let fiscal_year = FiscalYear::from(&FiscalYearEntity {
+            start: date(1, 1),
+            end: date(12, 31),
+            monthly: true,
+            locked: vec![1, 2],
+        })
+        .unwrap();
+        let check = |month: u32| {
+            super::check_new_transactions(
+                &[mock_transaction(
+                    "Kasse",
+                    "Bank",
+                    AmountEntity::new(1, 0),
+                    date(month, 1),
+                )],
+                &accounts,
+                &Currencies::default(),
+                Some(&fiscal_year),
+            )
+        };
 
-        let m: HashMap<String, LedgerAccount> = HashMap::from([
-            ("Cred".to_string(), dummy_ledger_account.clone()),
-            ("Deb".to_string(), dummy_ledger_account.clone()),
+        assert!(check(3).is_ok());
+        match check(2) {
+            Err(AccError::InvalidTransactions(errors)) => assert_eq!(
+                errors[0].problem,
+                TransactionProblem::LockedPeriod {
+                    number: 2,
+                    date: date(2, 1)
+                }
+            ),
+            other => panic!("expected locked period, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_calculate_transactions() -> Result<()> {
+        let accounts = accounts([
+            LedgerAccount::new(
+                "Kasse",
+                AmountEntity::new(100, 0),
+                AccountType::Balance(BalanceType::Active(ActiveType::WorkingCapital)),
+            ),
+            LedgerAccount::new(
+                "Fremdkapital",
+                AmountEntity::new(100, 0),
+                AccountType::Balance(BalanceType::Passive(PassiveType::DebtCapital)),
+            ),
+            LedgerAccount::new(
+                "Ertrag",
+                AmountEntity::zero(),
+                AccountType::Income(IncomeType::Revenue),
+            ),
         ]);
+        let transactions = vec![
+            mock_transaction("Kasse", "Ertrag", AmountEntity::new(20, 50), date(1, 1)),
+            mock_transaction(
+                "Fremdkapital",
+                "Kasse",
+                AmountEntity::new(30, 0),
+                date(2, 1),
+            ),
+        ];
+
+        let result =
+            super::calculate_transactions(&transactions, &accounts, &Currencies::default())?;
+
+        verify_amount(&result, "Kasse", AmountEntity::new(90, 50));
+        verify_amount(&result, "Fremdkapital", AmountEntity::new(70, 0));
+        verify_amount(&result, "Ertrag", AmountEntity::new(20, 50));
+
+        let result = super::calculate_transactions_until(
+            &transactions,
+            &accounts,
+            &Currencies::default(),
+            date(1, 31),
+        )?;
+
+        verify_amount(&result, "Kasse", AmountEntity::new(120, 50));
+        verify_amount(&result, "Fremdkapital", AmountEntity::new(100, 0));
+
+        let result = super::calculate_transactions_between(
+            &transactions,
+            &accounts,
+            &Currencies::default(),
+            date(2, 1),
+            date(2, 28),
+        )?;
+
+        verify_amount(&result, "Kasse", AmountEntity::new(70, 0));
+        let kasse = result.get_account("Kasse").unwrap();
+        assert_eq!(kasse.movement()?, AmountEntity::new(-30, 0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_exchange_difference_within_period() -> Result<()> {
+        let transactions = vec![
+            mock_compound_transaction(
+                vec![
+                    PostingLine::debit("Bank EUR", AmountEntity::new(1000, 0)).with_currency("EUR"),
+                    PostingLine::credit("Bank", AmountEntity::new(1000, 0)).with_currency("EUR"),
+                ],
+                date(2, 1),
+            ),
+            mock_compound_transaction(
+                vec![
+                    PostingLine::debit("Aufwand", AmountEntity::new(400, 0)).with_currency("EUR"),
+                    PostingLine::credit("Bank EUR", AmountEntity::new(400, 0)).with_currency("EUR"),
+                ],
+                date(7, 1),
+            ),
+        ];
+
+        let result = super::calculate_transactions_between(
+            &transactions,
+            &currency_accounts(),
+            &currencies(),
+            date(7, 1),
+            date(12, 31),
+        )?;
+
+        verify_amount(&result, "Kursdifferenzen", AmountEntity::new(20, 0));
+        verify_amount(&result, "Aufwand", AmountEntity::new(380, 0));
+        verify_amount(&result, "Bank EUR", AmountEntity::new(-400, 0));
+        verify_amount(&result, "Bank", AmountEntity::new(2000, 0));
+        assert_eq!(
+            result.get_account("Bank EUR").unwrap().foreign_amount(),
+            Some(AmountEntity::new(-400, 0))
+        );
+        Ok(())
+    }
+
+    fn compound_accounts() -> HashMap<String, LedgerAccount> {
+        accounts([
+            LedgerAccount::new(
+                "Kasse",
+                AmountEntity::zero(),
+                AccountType::Balance(BalanceType::Active(ActiveType::WorkingCapital)),
+            ),
+            LedgerAccount::new(
+                "Bank",
+                AmountEntity::zero(),
+                AccountType::Balance(BalanceType::Active(ActiveType::WorkingCapital)),
+            ),
+            LedgerAccount::new(
+                "Ertrag",
+                AmountEntity::zero(),
+                AccountType::Income(IncomeType::Revenue),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_calculate_compound_transaction() -> Result<()> {
+        let transactions = vec![mock_compound_transaction(
+            vec![
+                PostingLine::debit("Kasse", AmountEntity::new(30, 0)),
+                PostingLine::debit("Bank", AmountEntity::new(70, 0)),
+                PostingLine::credit("Ertrag", AmountEntity::new(100, 0)),
+            ],
+            date(1, 1),
+        )];
+
+        let result = super::calculate_transactions(
+            &transactions,
+            &compound_accounts(),
+            &Currencies::default(),
+        )?;
+
+        verify_amount(&result, "Kasse", AmountEntity::new(30, 0));
+        verify_amount(&result, "Bank", AmountEntity::new(70, 0));
+        verify_amount(&result, "Ertrag", AmountEntity::new(100, 0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_unbalanced_compound_transaction() {
+        let transactions = vec![mock_compound_transaction(
+            vec![
+                PostingLine::debit("Kasse", AmountEntity::new(30, 0)),
+                PostingLine::debit("Bank", AmountEntity::new(60, 0)),
+                PostingLine::credit("Ertrag", AmountEntity::new(100, 0)),
+            ],
+            date(1, 1),
+        )];
+
+        let errors = super::validate_transactions(
+            &transactions,
+            &compound_accounts(),
+            &Currencies::default(),
+            None,
+        );
+
+        assert_eq!(
+            errors[0].problem,
+            TransactionProblem::Unbalanced {
+                debit: AmountEntity::new(90, 0),
+                credit: AmountEntity::new(100, 0),
+            }
+        );
+    }
+
+    fn currency_accounts() -> HashMap<String, LedgerAccount> {
+        let working_capital = AccountType::Balance(BalanceType::Active(ActiveType::WorkingCapital));
+        accounts([
+            LedgerAccount::new("Bank", AmountEntity::new(2000, 0), working_capital.clone()),
+            LedgerAccount::new("Bank EUR", AmountEntity::zero(), working_capital)
+                .with_currency("EUR"),
+            LedgerAccount::new(
+                "Aufwand",
+                AmountEntity::zero(),
+                AccountType::Income(IncomeType::Expense),
+            ),
+            LedgerAccount::new(
+                "Kursdifferenzen",
+                AmountEntity::zero(),
+                AccountType::Income(IncomeType::Expense),
+            ),
+        ])
+    }
+
+    fn currencies() -> Currencies {
+        let rate = |month: u32, rate: &str| RateEntity {
+            currency: "EUR".to_string(),
+            date: date(month, 1),
+            rate: rate.parse().unwrap(),
+        };
+        Currencies::from(&CurrencyEntity {
+            base: "CHF".to_string(),
+            fx_account: Some("Kursdifferenzen".to_string()),
+            rates: vec![rate(1, "1.0"), rate(6, "0.95")],
+        })
+    }
 
-        mock_transaction
-            .expect_read_creditor_account()
-            .return_const(creditor_account.to_owned());
+    #[test]
+    fn test_realized_exchange_difference() -> Result<()> {
+        let transactions = vec![
+            mock_compound_transaction(
+                vec![
+                    PostingLine::debit("Bank EUR", AmountEntity::new(1000, 0)).with_currency("EUR"),
+                    PostingLine::credit("Bank", AmountEntity::new(1000, 0)).with_currency("EUR"),
+                ],
+                date(2, 1),
+            ),
+            mock_compound_transaction(
+                vec![
+                    PostingLine::debit("Aufwand", AmountEntity::new(400, 0)).with_currency("EUR"),
+                    PostingLine::credit("Bank EUR", AmountEntity::new(400, 0)).with_currency("EUR"),
+                ],
+                date(7, 1),
+            ),
+        ];
 
-        mock_transaction
-            .expect_read_debitor_account()
-            .return_const(debitor_account.to_owned());
+        let result =
+            super::calculate_transactions(&transactions, &currency_accounts(), &currencies())?;
 
+        verify_amount(&result, "Bank", AmountEntity::new(1000, 0));
+        verify_amount(&result, "Bank EUR", AmountEntity::new(600, 0));
+        verify_amount(&result, "Aufwand", AmountEntity::new(380, 0));
+        verify_amount(&result, "Kursdifferenzen", AmountEntity::new(20, 0));
         assert_eq!(
-            super::check_transactions(&vec![Box::new(mock_transaction)], &m).is_err(),
-            false
+            result.get_account("Bank EUR").unwrap().foreign_amount(),
+            Some(AmountEntity::new(600, 0))
         );
         Ok(())
     }
+
+    #[test]
+    fn test_exchange_difference_without_balance() -> Result<()> {
+        let transactions = vec![mock_compound_transaction(
+            vec![
+                PostingLine::debit("Aufwand", AmountEntity::new(400, 0)).with_currency("EUR"),
+                PostingLine::credit("Bank EUR", AmountEntity::new(400, 0)).with_currency("EUR"),
+            ],
+            date(7, 1),
+        )];
+
+        let result =
+            super::calculate_transactions(&transactions, &currency_accounts(), &currencies())?;
+
+        verify_amount(&result, "Bank EUR", AmountEntity::new(-380, 0));
+        verify_amount(&result, "Aufwand", AmountEntity::new(380, 0));
+        verify_amount(&result, "Kursdifferenzen", AmountEntity::zero());
+        assert_eq!(
+            result.get_account("Bank EUR").unwrap().foreign_amount(),
+            Some(AmountEntity::new(-400, 0))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_exchange_difference_when_overdrawn() -> Result<()> {
+        let transactions = vec![
+            mock_compound_transaction(
+                vec![
+                    PostingLine::debit("Bank EUR", AmountEntity::new(1000, 0)).with_currency("EUR"),
+                    PostingLine::credit("Bank", AmountEntity::new(1000, 0)).with_currency("EUR"),
+                ],
+                date(2, 1),
+            ),
+            mock_compound_transaction(
+                vec![
+                    PostingLine::debit("Aufwand", AmountEntity::new(1500, 0)).with_currency("EUR"),
+                    PostingLine::credit("Bank EUR", AmountEntity::new(1500, 0))
+                        .with_currency("EUR"),
+                ],
+                date(7, 1),
+            ),
+            mock_compound_transaction(
+                vec![
+                    PostingLine::debit("Bank EUR", AmountEntity::new(500, 0)).with_currency("EUR"),
+                    PostingLine::credit("Bank", AmountEntity::new(500, 0)).with_currency("EUR"),
+                ],
+                date(7, 1),
+            ),
+        ];
+
+        let result =
+            super::calculate_transactions(&transactions[..2], &currency_accounts(), &currencies())?;
+
+        verify_amount(&result, "Bank EUR", AmountEntity::new(-475, 0));
+        verify_amount(&result, "Aufwand", AmountEntity::new(1425, 0));
+        verify_amount(&result, "Kursdifferenzen", AmountEntity::new(50, 0));
+
+        let result =
+            super::calculate_transactions(&transactions, &currency_accounts(), &currencies())?;
+
+        verify_amount(&result, "Bank EUR", AmountEntity::zero());
+        verify_amount(&result, "Kursdifferenzen", AmountEntity::new(50, 0));
+        assert_eq!(
+            result.get_account("Bank EUR").unwrap().foreign_amount(),
+            Some(AmountEntity::zero())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_currency_problems() {
+        let transactions = vec![
+            mock_transaction("Bank EUR", "Bank", AmountEntity::new(10, 0), date(2, 1)),
+            mock_compound_transaction(
+                vec![
+                    PostingLine::debit("Aufwand", AmountEntity::new(10, 0)).with_currency("USD"),
+                    PostingLine::credit("Bank", AmountEntity::new(10, 0)).with_currency("USD"),
+                ],
+                date(2, 1),
+            ),
+        ];
+
+        let errors =
+            super::validate_transactions(&transactions, &currency_accounts(), &currencies(), None);
+
+        let problems: Vec<TransactionProblem> =
+            errors.into_iter().map(|error| error.problem).collect();
+        assert_eq!(
+            problems,
+            vec![
+                TransactionProblem::CurrencyMismatch {
+                    account: "Bank EUR".to_string(),
+                    currency: "CHF".to_string()
+                },
+                TransactionProblem::MissingRate {
+                    currency: "USD".to_string(),
+                    date: date(2, 1)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compound_transaction_balanced_in_foreign_currency() -> Result<()> {
+        let transactions = vec![mock_compound_transaction(
+            vec![
+                PostingLine::debit("Aufwand", AmountEntity::new(1, 5)).with_currency("EUR"),
+                PostingLine::debit("Aufwand", AmountEntity::new(1, 5)).with_currency("EUR"),
+                PostingLine::credit("Bank EUR", AmountEntity::new(2, 10)).with_currency("EUR"),
+            ],
+            date(1, 15),
+        )];
+        let currencies = Currencies::from(&CurrencyEntity {
+            base: "CHF".to_string(),
+            fx_account: Some("Kursdifferenzen".to_string()),
+            rates: vec![RateEntity {
+                currency: "EUR".to_string(),
+                date: date(1, 1),
+                rate: "0.93".parse().unwrap(),
+            }],
+        });
+
+        assert!(super::validate_transactions(
+            &transactions,
+            &currency_accounts(),
+            &currencies,
+            None
+        )
+        .is_empty());
+        let mut debit = AmountEntity::zero();
+        let mut credit = AmountEntity::zero();
+        super::book_transactions(
+            &transactions,
+            &currency_accounts(),
+            &currencies,
+            |_| true,
+            |booking| match booking.side {
+                PostingSide::Debit => debit = debit + booking.amount,
+                PostingSide::Credit => credit = credit + booking.amount,
+            },
+        )?;
+        assert_eq!(debit, AmountEntity::new(1, 95));
+        assert_eq!(credit, AmountEntity::new(1, 95));
+        Ok(())
+    }
+
+    #[test]
+    fn test_calculate_transactions_by_account_number() -> Result<()> {
+        let accounts = accounts([
+            LedgerAccount::new(
+                "Kasse",
+                AmountEntity::zero(),
+                AccountType::Balance(BalanceType::Active(ActiveType::WorkingCapital)),
+            )
+            .with_number(1000),
+            LedgerAccount::new(
+                "Ertrag",
+                AmountEntity::zero(),
+                AccountType::Income(IncomeType::Revenue),
+            )
+            .with_number(3000),
+        ]);
+        let transactions = vec![mock_transaction(
+            "1000",
+            "Ertrag",
+            AmountEntity::new(10, 0),
+            date(1, 1),
+        )];
+
+        let result =
+            super::calculate_transactions(&transactions, &accounts, &Currencies::default())?;
+
+        verify_amount(&result, "Kasse", AmountEntity::new(10, 0));
+        verify_amount(&result, "3000", AmountEntity::new(10, 0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_sub_accounts_roll_up() -> Result<()> {
+        let bank_type = AccountType::Balance(BalanceType::Active(ActiveType::WorkingCapital));
+        let accounts = accounts([
+            LedgerAccount::new("Bank", AmountEntity::zero(), bank_type.clone()),
+            LedgerAccount::new("Bank:UBS", AmountEntity::new(10, 0), bank_type.clone())
+                .with_parent("Bank"),
+            LedgerAccount::new(
+                "Bank:PostFinance",
+                AmountEntity::new(5, 0),
+                bank_type.clone(),
+            )
+            .with_parent("Bank"),
+            LedgerAccount::new(
+                "Ertrag",
+                AmountEntity::zero(),
+                AccountType::Income(IncomeType::Revenue),
+            ),
+        ]);
+        let transactions = vec![mock_transaction(
+            "Bank:UBS",
+            "Ertrag",
+            AmountEntity::new(1, 0),
+            date(1, 1),
+        )];
+
+        let result =
+            super::calculate_transactions(&transactions, &accounts, &Currencies::default())?;
+
+        let tree = result.account_tree(&bank_type);
+        let names: Vec<(usize, &str)> = tree
+            .iter()
+            .map(|(depth, account)| (*depth, account.name()))
+            .collect();
+        assert_eq!(
+            names,
+            vec![(0, "Bank"), (1, "Bank:PostFinance"), (1, "Bank:UBS")]
+        );
+        assert_eq!(
+            result.rolled_up(tree[0].1, &|account| Ok(account.current_amount()))?,
+            AmountEntity::new(16, 0)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_booking_onto_parent_account() {
+        let bank_type = AccountType::Balance(BalanceType::Active(ActiveType::WorkingCapital));
+        let accounts = accounts([
+            LedgerAccount::new("Bank", AmountEntity::zero(), bank_type.clone()),
+            LedgerAccount::new("Bank:UBS", AmountEntity::zero(), bank_type).with_parent("Bank"),
+            LedgerAccount::new(
+                "Ertrag",
+                AmountEntity::zero(),
+                AccountType::Income(IncomeType::Revenue),
+            ),
+        ]);
+        let transactions = vec![mock_transaction(
+            "Bank",
+            "Ertrag",
+            AmountEntity::new(1, 0),
+            date(1, 1),
+        )];
+
+        let errors =
+            super::validate_transactions(&transactions, &accounts, &Currencies::default(), None);
+
+        assert_eq!(
+            errors[0].problem,
+            TransactionProblem::ParentAccount("Bank".to_string())
+        );
+    }
+
+    #[test]
+    fn test_calculate_transactions_unknown_account() {
+        let accounts = HashMap::from([("Kasse".to_string(), LedgerAccount::dummy())]);
+        let transactions = vec![mock_transaction(
+            "Kasse",
+            "Unbekannt",
+            AmountEntity::new(1, 0),
+            date(1, 1),
+        )];
+        assert!(
+            super::calculate_transactions(&transactions, &accounts, &Currencies::default())
+                .is_err()
+        );
+    }
+
+    fn verify_amount(result: &super::TransactionResult, name: &str, amount: AmountEntity) {
+        let account = result
+            .get_account(name)
+            .unwrap_or_else(|| panic!("cannot find account with name {name}"));
+        assert_eq!(account.current_amount(), amount);
+    }
 }
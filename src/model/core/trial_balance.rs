@@ -0,0 +1,276 @@
+use super::account::LedgerAccount;
+use super::currency::Currencies;
+use super::transaction::book_transactions;
+use crate::model::entity::{AmountEntity, PostingSide, TransactionAccountReader};
+use crate::model::error::Result;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+const REPORT_WIDTH: usize = 86;
+
+/// Opening balance, movements and closing balance of one account in base currency.
+#[derive(Clone, PartialEq, Debug)]
+pub struct TrialBalanceLine {
+    label: String,
+    increases_on_debit: bool,
+    opening: AmountEntity,
+    debit: AmountEntity,
+    credit: AmountEntity,
+    closing: AmountEntity,
+}
+
+impl TrialBalanceLine {
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn opening(&self) -> AmountEntity {
+        self.opening
+    }
+
+    pub fn debit(&self) -> AmountEntity {
+        self.debit
+    }
+
+    pub fn credit(&self) -> AmountEntity {
+        self.credit
+    }
+
+    pub fn closing(&self) -> AmountEntity {
+        self.closing
+    }
+
+    /// Balance with debit balances positive and credit balances negative.
+    fn debit_balance(&self, balance: AmountEntity) -> AmountEntity {
+        if self.increases_on_debit {
+            balance
+        } else {
+            -balance
+        }
+    }
+}
+
+/// Trial balance (Saldenliste) of all accounts sorted by number.
+/// Debit and credit totals match for every correctly booked ledger.
+#[derive(Clone, PartialEq, Debug)]
+pub struct TrialBalance {
+    lines: Vec<TrialBalanceLine>,
+    opening_total: AmountEntity,
+    debit_total: AmountEntity,
+    credit_total: AmountEntity,
+    closing_total: AmountEntity,
+}
+
+impl TrialBalance {
+    pub fn from(
+        transactions: &[Box<dyn TransactionAccountReader>],
+        accounts: &HashMap<String, LedgerAccount>,
+        currencies: &Currencies,
+    ) -> Result<Self> {
+        let mut bookings = vec![];
+        let result = book_transactions(
+            transactions,
+            accounts,
+            currencies,
+            |_| true,
+            |booking| bookings.push(booking),
+        )?;
+        let mut movements: HashMap<String, (AmountEntity, AmountEntity)> = HashMap::new();
+        for booking in bookings {
+            let (debit, credit) = movements.entry(booking.account).or_default();
+            match booking.side {
+                PostingSide::Debit => *debit = debit.checked_add(booking.amount)?,
+                PostingSide::Credit => *credit = credit.checked_add(booking.amount)?,
+            }
+        }
+        let mut calculated: Vec<_> = result.accounts().collect();
+        calculated.sort_by(|a, b| a.cmp_by_number(b));
+        let lines: Vec<TrialBalanceLine> = calculated
+            .into_iter()
+            .map(|account| {
+                let (debit, credit) = movements.get(account.name()).copied().unwrap_or_default();
+                TrialBalanceLine {
+                    label: account.label(),
+                    increases_on_debit: account.account_type().increases_on_debit(),
+                    opening: account.start_amount(),
+                    debit,
+                    credit,
+                    closing: account.current_amount(),
+                }
+            })
+            .collect();
+        let total = |amount: &dyn Fn(&TrialBalanceLine) -> AmountEntity| {
+            AmountEntity::checked_sum(lines.iter().map(amount))
+        };
+        Ok(TrialBalance {
+            opening_total: total(&|line| line.debit_balance(line.opening))?,
+            debit_total: total(&|line| line.debit)?,
+            credit_total: total(&|line| line.credit)?,
+            closing_total: total(&|line| line.debit_balance(line.closing))?,
+            lines,
+        })
+    }
+
+    pub fn lines(&self) -> &[TrialBalanceLine] {
+        &self.lines
+    }
+
+    pub fn debit_total(&self) -> AmountEntity {
+        self.debit_total
+    }
+
+    pub fn credit_total(&self) -> AmountEntity {
+        self.credit_total
+    }
+
+    /// Sum of the opening balances with debit balances positive, zero if the opening balances are consistent.
+    pub fn opening_total(&self) -> AmountEntity {
+        self.opening_total
+    }
+
+    /// Sum of the closing balances with debit balances positive, zero if the opening balances are consistent.
+    pub fn closing_total(&self) -> AmountEntity {
+        self.closing_total
+    }
+
+    pub fn is_balanced(&self) -> bool {
+        self.debit_total() == self.credit_total()
+    }
+}
+
+impl Display for TrialBalance {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Trial Balance")?;
+        writeln!(f, "{}", "=".repeat(REPORT_WIDTH))?;
+        writeln!(
+            f,
+            "{:<30}{:>14}{:>14}{:>14}{:>14}",
+            "Account", "Opening", "Debit", "Credit", "Closing"
+        )?;
+        for line in self.lines.iter() {
+            writeln!(
+                f,
+                "{:<30}{:>14}{:>14}{:>14}{:>14}",
+                line.label, line.opening, line.debit, line.credit, line.closing
+            )?;
+        }
+        writeln!(f, "{}", "-".repeat(REPORT_WIDTH))?;
+        writeln!(
+            f,
+            "{:<30}{:>14}{:>14}{:>14}{:>14}",
+            "Total",
+            self.opening_total(),
+            self.debit_total(),
+            self.credit_total(),
+            self.closing_total()
+        )?;
+        if let Ok(difference) = self.debit_total.checked_sub(self.credit_total) {
+            if !difference.is_zero() {
+                writeln!(f, "Debit and credit totals differ by {difference}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TrialBalance;
+    use crate::model::core::account::{
+        AccountType, ActiveType, BalanceType, IncomeType, LedgerAccount, PassiveType,
+    };
+    use crate::model::core::currency::Currencies;
+    use crate::model::core::test_support::{accounts, date, mock_compound_transaction};
+    use crate::model::entity::{AmountEntity, CurrencyEntity, PostingLine, RateEntity};
+    use crate::model::error::Result;
+    use std::collections::HashMap;
+
+    fn trial_accounts() -> HashMap<String, LedgerAccount> {
+        let working_capital = AccountType::Balance(BalanceType::Active(ActiveType::WorkingCapital));
+        accounts([
+            LedgerAccount::new("Bank", AmountEntity::new(2000, 0), working_capital.clone())
+                .with_number(1020),
+            LedgerAccount::new("Bank EUR", AmountEntity::zero(), working_capital)
+                .with_currency("EUR")
+                .with_number(1021),
+            LedgerAccount::new(
+                "Eigenkapital",
+                AmountEntity::new(2000, 0),
+                AccountType::Balance(BalanceType::Passive(PassiveType::Equity)),
+            )
+            .with_number(2800),
+            LedgerAccount::new(
+                "Aufwand",
+                AmountEntity::zero(),
+                AccountType::Income(IncomeType::Expense),
+            )
+            .with_number(6000),
+            LedgerAccount::new(
+                "Kursdifferenzen",
+                AmountEntity::zero(),
+                AccountType::Income(IncomeType::Expense),
+            )
+            .with_number(6900),
+        ])
+    }
+
+    fn currencies() -> Currencies {
+        let rate = |month: u32, rate: &str| RateEntity {
+            currency: "EUR".to_string(),
+            date: date(month, 1),
+            rate: rate.parse().unwrap(),
+        };
+        Currencies::from(&CurrencyEntity {
+            base: "CHF".to_string(),
+            fx_account: Some("Kursdifferenzen".to_string()),
+            rates: vec![rate(1, "1.0"), rate(6, "0.95")],
+        })
+    }
+
+    #[test]
+    fn test_trial_balance_totals_match() -> Result<()> {
+        let transactions = vec![
+            mock_compound_transaction(
+                vec![
+                    PostingLine::debit("Bank EUR", AmountEntity::new(1000, 0)).with_currency("EUR"),
+                    PostingLine::credit("Bank", AmountEntity::new(1000, 0)).with_currency("EUR"),
+                ],
+                date(2, 1),
+            ),
+            mock_compound_transaction(
+                vec![
+                    PostingLine::debit("Aufwand", AmountEntity::new(400, 0)).with_currency("EUR"),
+                    PostingLine::credit("Bank EUR", AmountEntity::new(400, 0)).with_currency("EUR"),
+                ],
+                date(7, 1),
+            ),
+        ];
+
+        let trial_balance = TrialBalance::from(&transactions, &trial_accounts(), &currencies())?;
+
+        let labels: Vec<&str> = trial_balance
+            .lines()
+            .iter()
+            .map(|line| line.label())
+            .collect();
+        assert_eq!(
+            labels,
+            vec![
+                "1020 Bank",
+                "1021 Bank EUR",
+                "2800 Eigenkapital",
+                "6000 Aufwand",
+                "6900 Kursdifferenzen"
+            ]
+        );
+        let bank_eur = &trial_balance.lines()[1];
+        assert_eq!(bank_eur.debit(), AmountEntity::new(1000, 0));
+        assert_eq!(bank_eur.credit(), AmountEntity::new(400, 0));
+        assert_eq!(bank_eur.closing(), AmountEntity::new(600, 0));
+        assert_eq!(trial_balance.debit_total(), AmountEntity::new(1400, 0));
+        assert!(trial_balance.is_balanced());
+        assert_eq!(trial_balance.opening_total(), AmountEntity::zero());
+        assert_eq!(trial_balance.closing_total(), AmountEntity::zero());
+        Ok(())
+    }
+}
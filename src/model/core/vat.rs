@@ -0,0 +1,719 @@
+use super::account::{
+    find_account, AccountType, ActiveType, BalanceType, IncomeType, LedgerAccount,
+};
+use super::currency::Currencies;
+use crate::model::entity::{
+    AmountEntity, Percentage, PostingLine, PostingSide, TaxCode, TransactionAccountReader,
+    VatEntity,
+};
+use crate::model::error::{AccError, Result};
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
+
+const REPORT_WIDTH: usize = 50;
+
+/// Swiss VAT rates in basis points per tax code with the date they are valid from.
+const SWISS_RATES: [(TaxCode, (i32, u32, u32), i64); 9] = [
+    (TaxCode::Standard, (2011, 1, 1), 800),
+    (TaxCode::Standard, (2018, 1, 1), 770),
+    (TaxCode::Standard, (2024, 1, 1), 810),
+    (TaxCode::Reduced, (2011, 1, 1), 250),
+    (TaxCode::Reduced, (2018, 1, 1), 250),
+    (TaxCode::Reduced, (2024, 1, 1), 260),
+    (TaxCode::Accommodation, (2011, 1, 1), 380),
+    (TaxCode::Accommodation, (2018, 1, 1), 370),
+    (TaxCode::Accommodation, (2024, 1, 1), 380),
+];
+
+/// VAT rates per tax code, the Swiss rates together with the rates of the VAT definition.
+#[derive(Clone, PartialEq, Debug)]
+pub struct VatRates {
+    /// Rates per tax code, sorted by the date they are valid from.
+    rates: BTreeMap<TaxCode, Vec<(NaiveDate, Percentage)>>,
+}
+
+impl VatRates {
+    /// A defined rate replaces the Swiss rate valid from the same date.
+    pub fn from(vat: Option<&VatEntity>) -> Self {
+        let mut rates: BTreeMap<TaxCode, Vec<(NaiveDate, Percentage)>> = BTreeMap::new();
+        for (code, (year, month, day), basis_points) in SWISS_RATES {
+            if let Some(date) = NaiveDate::from_ymd_opt(year, month, day) {
+                rates
+                    .entry(code)
+                    .or_default()
+                    .push((date, Percentage::from_basis_points(basis_points)));
+            }
+        }
+        for rate in vat.iter().flat_map(|vat| vat.rates.iter()) {
+            let code_rates = rates.entry(rate.code).or_default();
+            code_rates.retain(|(date, _)| *date != rate.date);
+            code_rates.push((rate.date, rate.rate));
+        }
+        for code_rates in rates.values_mut() {
+            code_rates.sort_by_key(|(date, _)| *date);
+        }
+        VatRates { rates }
+    }
+
+    /// Rate valid at `date`, which is the latest rate defined on or before that date.
+    /// Exempt amounts have a rate of zero.
+    pub fn rate(&self, code: TaxCode, date: NaiveDate) -> Result<Percentage> {
+        if code == TaxCode::Exempt {
+            return Ok(Percentage::default());
+        }
+        self.rates
+            .get(&code)
+            .and_then(|rates| {
+                rates
+                    .iter()
+                    .rev()
+                    .find(|(valid_from, _)| *valid_from <= date)
+            })
+            .map(|(_, rate)| *rate)
+            .ok_or(AccError::MissingVatRate { code, date })
+    }
+}
+
+impl Default for VatRates {
+    fn default() -> Self {
+        VatRates::from(None)
+    }
+}
+
+/// Splits a gross amount into net amount and tax, the tax is rounded to the nearest minor unit.
+fn split_gross(gross: AmountEntity, rate: Percentage) -> Result<(AmountEntity, AmountEntity)> {
+    let tax = gross.scale(rate.basis_points(), 10_000 + rate.basis_points())?;
+    Ok((gross.checked_sub(tax)?, tax))
+}
+
+/// Whether the tax of a line is owed on sales or deducted on purchases.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum TaxKind {
+    Output,
+    Input,
+}
+
+/// Tax code and kind of a line whose tax is split off. Only lines on income and fixed asset accounts are
+/// split, all other balance accounts settle or carry the gross amount. Tax on revenue is output tax, tax on
+/// expenses and fixed assets is input tax, whichever side the line is posted on.
+fn taxed_line(
+    line: &PostingLine,
+    accounts: &HashMap<String, LedgerAccount>,
+) -> Option<(TaxCode, TaxKind)> {
+    let code = line.tax?;
+    match find_account(accounts, &line.account)?.account_type() {
+        AccountType::Income(IncomeType::Revenue) => Some((code, TaxKind::Output)),
+        AccountType::Income(IncomeType::Expense)
+        | AccountType::Balance(BalanceType::Active(ActiveType::FixedAssets)) => {
+            Some((code, TaxKind::Input))
+        }
+        AccountType::Balance(_) => None,
+    }
+}
+
+/// Transaction with its tax split off onto the input-tax and output-tax accounts.
+struct TaxedTransaction {
+    description: String,
+    date: NaiveDate,
+    lines: Vec<PostingLine>,
+}
+
+impl TransactionAccountReader for TaxedTransaction {
+    fn read_description(&self) -> &str {
+        &self.description
+    }
+    fn read_date(&self) -> NaiveDate {
+        self.date
+    }
+    fn read_lines(&self) -> Vec<PostingLine> {
+        self.lines.clone()
+    }
+}
+
+/// Replaces every taxed gross line by its net line and a tax line on the same side, booked onto the input-tax
+/// or output-tax account by the kind of the tax. Transactions without tax are passed on unchanged.
+pub fn split_tax(
+    transactions: Vec<Box<dyn TransactionAccountReader>>,
+    accounts: &HashMap<String, LedgerAccount>,
+    vat: Option<&VatEntity>,
+) -> Result<Vec<Box<dyn TransactionAccountReader>>> {
+    let rates = VatRates::from(vat);
+    transactions
+        .into_iter()
+        .map(|transaction| {
+            let lines = transaction.read_lines();
+            if !lines.iter().any(|line| {
+                taxed_line(line, accounts).is_some_and(|(code, _)| code != TaxCode::Exempt)
+            }) {
+                return Ok(transaction);
+            }
+            let vat = vat.ok_or(AccError::MissingVatAccounts)?;
+            let date = transaction.read_date();
+            let mut booked = vec![];
+            for line in lines {
+                match taxed_line(&line, accounts) {
+                    Some((code, kind)) if code != TaxCode::Exempt => {
+                        let (net, tax) = split_gross(line.amount, rates.rate(code, date)?)?;
+                        let tax_account = match kind {
+                            TaxKind::Input => &vat.input_tax,
+                            TaxKind::Output => &vat.output_tax,
+                        };
+                        let tax_line = PostingLine {
+                            account: tax_account.clone(),
+                            amount: tax,
+                            tax: None,
+                            ..line.clone()
+                        };
+                        booked.push(PostingLine {
+                            amount: net,
+                            ..line
+                        });
+                        if !tax.is_zero() {
+                            booked.push(tax_line);
+                        }
+                    }
+                    _ => booked.push(line),
+                }
+            }
+            Ok(Box::new(TaxedTransaction {
+                description: transaction.read_description().to_string(),
+                date,
+                lines: booked,
+            }) as Box<dyn TransactionAccountReader>)
+        })
+        .collect()
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct VatLine {
+    code: TaxCode,
+    rate: Percentage,
+    turnover: AmountEntity,
+    tax: AmountEntity,
+}
+
+impl VatLine {
+    pub fn code(&self) -> TaxCode {
+        self.code
+    }
+
+    pub fn rate(&self) -> Percentage {
+        self.rate
+    }
+
+    /// Net amount in base currency.
+    pub fn turnover(&self) -> AmountEntity {
+        self.turnover
+    }
+
+    pub fn tax(&self) -> AmountEntity {
+        self.tax
+    }
+}
+
+/// VAT return (MWST-Abrechnung) summing turnover and tax per tax code within a period, usually a quarter.
+#[derive(Clone, PartialEq, Debug)]
+pub struct VatReturn {
+    from: NaiveDate,
+    to: NaiveDate,
+    output: Vec<VatLine>,
+    input: Vec<VatLine>,
+    output_tax: AmountEntity,
+    input_tax: AmountEntity,
+    payable: AmountEntity,
+}
+
+impl VatReturn {
+    /// Builds the return from the transactions as read from the ledger, before their tax is split off.
+    /// Credit notes on sales and returns of purchases reduce the turnover and tax of their own section.
+    pub fn from(
+        transactions: &[Box<dyn TransactionAccountReader>],
+        accounts: &HashMap<String, LedgerAccount>,
+        currencies: &Currencies,
+        rates: &VatRates,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Self> {
+        let mut output: BTreeMap<(TaxCode, Percentage), (AmountEntity, AmountEntity)> =
+            BTreeMap::new();
+        let mut input: BTreeMap<(TaxCode, Percentage), (AmountEntity, AmountEntity)> =
+            BTreeMap::new();
+        for transaction in transactions.iter() {
+            let date = transaction.read_date();
+            if date < from || to < date {
+                continue;
+            }
+            for line in transaction.read_lines() {
+                let Some((code, kind)) = taxed_line(&line, accounts) else {
+                    continue;
+                };
+                let gross = currencies.to_base(line.amount, line.currency.as_deref(), date)?;
+                let rate = rates.rate(code, date)?;
+                let (net, tax) = split_gross(gross, rate)?;
+                let (totals, increasing_side) = match kind {
+                    TaxKind::Input => (input.entry((code, rate)).or_default(), PostingSide::Debit),
+                    TaxKind::Output => {
+                        (output.entry((code, rate)).or_default(), PostingSide::Credit)
+                    }
+                };
+                let (net, tax) = if line.side == increasing_side {
+                    (net, tax)
+                } else {
+                    (-net, -tax)
+                };
+                totals.0 = totals.0.checked_add(net)?;
+                totals.1 = totals.1.checked_add(tax)?;
+            }
+        }
+        let lines = |totals: BTreeMap<(TaxCode, Percentage), (AmountEntity, AmountEntity)>| {
+            totals
+                .into_iter()
+                .map(|((code, rate), (turnover, tax))| VatLine {
+                    code,
+                    rate,
+                    turnover,
+                    tax,
+                })
+                .collect()
+        };
+        let output: Vec<VatLine> = lines(output);
+        let input: Vec<VatLine> = lines(input);
+        let output_tax = AmountEntity::checked_sum(output.iter().map(VatLine::tax))?;
+        let input_tax = AmountEntity::checked_sum(input.iter().map(VatLine::tax))?;
+        Ok(VatReturn {
+            from,
+            to,
+            output,
+            input,
+            output_tax,
+            input_tax,
+            payable: output_tax.checked_sub(input_tax)?,
+        })
+    }
+
+    pub fn from_date(&self) -> NaiveDate {
+        self.from
+    }
+
+    pub fn to_date(&self) -> NaiveDate {
+        self.to
+    }
+
+    /// Sales per tax code and rate.
+    pub fn output(&self) -> &[VatLine] {
+        &self.output
+    }
+
+    /// Purchases per tax code and rate.
+    pub fn input(&self) -> &[VatLine] {
+        &self.input
+    }
+
+    pub fn output_tax(&self) -> AmountEntity {
+        self.output_tax
+    }
+
+    pub fn input_tax(&self) -> AmountEntity {
+        self.input_tax
+    }
+
+    /// Positive if tax has to be paid, negative for a refund.
+    pub fn payable(&self) -> AmountEntity {
+        self.payable
+    }
+}
+
+fn write_lines(
+    f: &mut Formatter<'_>,
+    title: &str,
+    lines: &[VatLine],
+    total: AmountEntity,
+) -> std::fmt::Result {
+    writeln!(f, "{title}")?;
+    for line in lines.iter() {
+        writeln!(
+            f,
+            "    {:<16}{:>6}{:>12}{:>12}",
+            line.code.to_string(),
+            format!("{}%", line.rate),
+            line.turnover,
+            line.tax
+        )?;
+    }
+    writeln!(f, "  {:<36}{:>12}", format!("Total {title}"), total)
+}
+
+impl Display for VatReturn {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "VAT Return {} - {}", self.from, self.to)?;
+        writeln!(f, "{}", "=".repeat(REPORT_WIDTH))?;
+        writeln!(
+            f,
+            "    {:<16}{:>6}{:>12}{:>12}",
+            "Code", "Rate", "Turnover", "Tax"
+        )?;
+        write_lines(f, "Output Tax", &self.output, self.output_tax())?;
+        write_lines(f, "Input Tax", &self.input, self.input_tax())?;
+        writeln!(f, "{}", "-".repeat(REPORT_WIDTH))?;
+        let payable = self.payable();
+        let label = if payable.is_negative() {
+            "Refundable Tax"
+        } else {
+            "Payable Tax"
+        };
+        writeln!(f, "{label:<38}{:>12}", payable.abs())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{split_gross, split_tax, VatRates, VatReturn};
+    use crate::model::core::account::{
+        AccountType, ActiveType, BalanceType, LedgerAccount, PassiveType,
+    };
+    use crate::model::core::currency::Currencies;
+    use crate::model::core::test_support::{date, ledger_accounts, mock_compound_transaction};
+    use crate::model::entity::{
+        AmountEntity, Percentage, PostingLine, TaxCode, TransactionAccountReader, VatEntity,
+        VatRateEntity,
+    };
+    use crate::model::error::{AccError, Result};
+    use chrono::NaiveDate;
+    use std::collections::HashMap;
+
+    fn vat() -> VatEntity {
+        VatEntity {
+            input_tax: "Vorsteuer".to_string(),
+            output_tax: "Umsatzsteuer".to_string(),
+            rates: vec![],
+        }
+    }
+
+    fn vat_accounts() -> HashMap<String, LedgerAccount> {
+        ledger_accounts([
+            LedgerAccount::new(
+                "Vorsteuer",
+                AmountEntity::zero(),
+                AccountType::Balance(BalanceType::Active(ActiveType::WorkingCapital)),
+            ),
+            LedgerAccount::new(
+                "Umsatzsteuer",
+                AmountEntity::zero(),
+                AccountType::Balance(BalanceType::Passive(PassiveType::DebtCapital)),
+            ),
+            LedgerAccount::new(
+                "Maschinen",
+                AmountEntity::zero(),
+                AccountType::Balance(BalanceType::Active(ActiveType::FixedAssets)),
+            ),
+        ])
+    }
+
+    fn taxed_transaction(
+        debitor: &str,
+        creditor: &str,
+        amount: AmountEntity,
+        tax: TaxCode,
+        month: u32,
+    ) -> Box<dyn TransactionAccountReader> {
+        mock_compound_transaction(
+            vec![
+                PostingLine::debit(debitor, amount).with_tax(tax),
+                PostingLine::credit(creditor, amount).with_tax(tax),
+            ],
+            date(month, 15),
+        )
+    }
+
+    #[test]
+    fn test_vat_rates_valid_at_date() -> Result<()> {
+        let date = |year: i32| NaiveDate::from_ymd_opt(year, 6, 30).unwrap();
+        let rates = VatRates::from(Some(&VatEntity {
+            rates: vec![VatRateEntity {
+                code: TaxCode::Standard,
+                date: NaiveDate::from_ymd_opt(2030, 1, 1).unwrap(),
+                rate: Percentage::from_basis_points(850),
+            }],
+            ..vat()
+        }));
+
+        assert_eq!(
+            rates.rate(TaxCode::Standard, date(2022))?.basis_points(),
+            770
+        );
+        assert_eq!(
+            rates.rate(TaxCode::Standard, date(2024))?.basis_points(),
+            810
+        );
+        assert_eq!(
+            rates.rate(TaxCode::Standard, date(2030))?.basis_points(),
+            850
+        );
+        assert_eq!(
+            rates.rate(TaxCode::Reduced, date(2030))?.basis_points(),
+            260
+        );
+        assert!(rates.rate(TaxCode::Exempt, date(2000))?.is_zero());
+        assert!(matches!(
+            rates.rate(TaxCode::Standard, date(2000)),
+            Err(AccError::MissingVatRate { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_gross_rounds_tax() -> Result<()> {
+        let (net, tax) = split_gross(
+            AmountEntity::new(100, 0),
+            Percentage::from_basis_points(770),
+        )?;
+
+        assert_eq!(tax, AmountEntity::new(7, 15));
+        assert_eq!(net, AmountEntity::new(92, 85));
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_tax() -> Result<()> {
+        let transactions = vec![
+            taxed_transaction(
+                "Kasse",
+                "Ertrag",
+                AmountEntity::new(107, 70),
+                TaxCode::Standard,
+                1,
+            ),
+            taxed_transaction(
+                "Aufwand",
+                "Kasse",
+                AmountEntity::new(102, 50),
+                TaxCode::Reduced,
+                2,
+            ),
+        ];
+
+        let booked = split_tax(transactions, &vat_accounts(), Some(&vat()))?;
+
+        assert_eq!(
+            booked[0].read_lines(),
+            vec![
+                PostingLine::debit("Kasse", AmountEntity::new(107, 70)).with_tax(TaxCode::Standard),
+                PostingLine::credit("Ertrag", AmountEntity::new(100, 0))
+                    .with_tax(TaxCode::Standard),
+                PostingLine::credit("Umsatzsteuer", AmountEntity::new(7, 70)),
+            ]
+        );
+        assert_eq!(
+            booked[1].read_lines(),
+            vec![
+                PostingLine::debit("Aufwand", AmountEntity::new(100, 0)).with_tax(TaxCode::Reduced),
+                PostingLine::debit("Vorsteuer", AmountEntity::new(2, 50)),
+                PostingLine::credit("Kasse", AmountEntity::new(102, 50)).with_tax(TaxCode::Reduced),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_tax_only_on_income_and_fixed_assets() -> Result<()> {
+        let transactions = vec![
+            taxed_transaction(
+                "Kasse",
+                "Eigenkapital",
+                AmountEntity::new(107, 70),
+                TaxCode::Standard,
+                1,
+            ),
+            taxed_transaction(
+                "Maschinen",
+                "Kasse",
+                AmountEntity::new(107, 70),
+                TaxCode::Standard,
+                1,
+            ),
+        ];
+
+        let booked = split_tax(transactions, &vat_accounts(), Some(&vat()))?;
+
+        assert_eq!(booked[0].read_lines().len(), 2);
+        assert_eq!(
+            booked[1].read_lines()[..2],
+            [
+                PostingLine::debit("Maschinen", AmountEntity::new(100, 0))
+                    .with_tax(TaxCode::Standard),
+                PostingLine::debit("Vorsteuer", AmountEntity::new(7, 70)),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_tax_of_credit_note_and_return() -> Result<()> {
+        let transactions = vec![
+            taxed_transaction(
+                "Ertrag",
+                "Kasse",
+                AmountEntity::new(107, 70),
+                TaxCode::Standard,
+                1,
+            ),
+            taxed_transaction(
+                "Kasse",
+                "Aufwand",
+                AmountEntity::new(102, 50),
+                TaxCode::Reduced,
+                2,
+            ),
+        ];
+
+        let booked = split_tax(transactions, &vat_accounts(), Some(&vat()))?;
+
+        assert_eq!(
+            booked[0].read_lines()[..2],
+            [
+                PostingLine::debit("Ertrag", AmountEntity::new(100, 0)).with_tax(TaxCode::Standard),
+                PostingLine::debit("Umsatzsteuer", AmountEntity::new(7, 70)),
+            ]
+        );
+        assert_eq!(
+            booked[1].read_lines()[1..],
+            [
+                PostingLine::credit("Aufwand", AmountEntity::new(100, 0))
+                    .with_tax(TaxCode::Reduced),
+                PostingLine::credit("Vorsteuer", AmountEntity::new(2, 50)),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_tax_without_vat_accounts() {
+        let transactions = vec![taxed_transaction(
+            "Kasse",
+            "Ertrag",
+            AmountEntity::new(108, 10),
+            TaxCode::Standard,
+            1,
+        )];
+
+        assert!(matches!(
+            split_tax(transactions, &vat_accounts(), None),
+            Err(AccError::MissingVatAccounts)
+        ));
+    }
+
+    #[test]
+    fn test_vat_return() -> Result<()> {
+        let transactions = vec![
+            taxed_transaction(
+                "Kasse",
+                "Ertrag",
+                AmountEntity::new(107, 70),
+                TaxCode::Standard,
+                1,
+            ),
+            taxed_transaction(
+                "Kasse",
+                "Ertrag",
+                AmountEntity::new(215, 40),
+                TaxCode::Standard,
+                3,
+            ),
+            taxed_transaction(
+                "Kasse",
+                "Ertrag",
+                AmountEntity::new(50, 0),
+                TaxCode::Exempt,
+                2,
+            ),
+            taxed_transaction(
+                "Aufwand",
+                "Kasse",
+                AmountEntity::new(103, 70),
+                TaxCode::Accommodation,
+                2,
+            ),
+            taxed_transaction(
+                "Kasse",
+                "Ertrag",
+                AmountEntity::new(108, 10),
+                TaxCode::Standard,
+                4,
+            ),
+        ];
+
+        let vat_return = VatReturn::from(
+            &transactions,
+            &vat_accounts(),
+            &Currencies::default(),
+            &VatRates::default(),
+            date(1, 1),
+            date(3, 31),
+        )?;
+
+        assert_eq!(vat_return.output().len(), 2);
+        assert_eq!(vat_return.output()[0].code(), TaxCode::Standard);
+        assert_eq!(vat_return.output()[0].turnover(), AmountEntity::new(300, 0));
+        assert_eq!(vat_return.output()[0].rate().basis_points(), 770);
+        assert_eq!(vat_return.output()[0].tax(), AmountEntity::new(23, 10));
+        assert_eq!(vat_return.output()[1].turnover(), AmountEntity::new(50, 0));
+        assert_eq!(vat_return.input_tax(), AmountEntity::new(3, 70));
+        assert_eq!(vat_return.payable(), AmountEntity::new(19, 40));
+        assert!(vat_return.to_string().contains("7.7%"));
+        assert!(vat_return.to_string().contains("Payable Tax"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_vat_return_with_credit_note_and_return() -> Result<()> {
+        let transactions = vec![
+            taxed_transaction(
+                "Kasse",
+                "Ertrag",
+                AmountEntity::new(215, 40),
+                TaxCode::Standard,
+                1,
+            ),
+            taxed_transaction(
+                "Ertrag",
+                "Kasse",
+                AmountEntity::new(107, 70),
+                TaxCode::Standard,
+                2,
+            ),
+            taxed_transaction(
+                "Aufwand",
+                "Kasse",
+                AmountEntity::new(205, 0),
+                TaxCode::Reduced,
+                2,
+            ),
+            taxed_transaction(
+                "Kasse",
+                "Aufwand",
+                AmountEntity::new(102, 50),
+                TaxCode::Reduced,
+                3,
+            ),
+        ];
+
+        let vat_return = VatReturn::from(
+            &transactions,
+            &vat_accounts(),
+            &Currencies::default(),
+            &VatRates::default(),
+            date(1, 1),
+            date(3, 31),
+        )?;
+
+        assert_eq!(vat_return.output().len(), 1);
+        assert_eq!(vat_return.output()[0].turnover(), AmountEntity::new(100, 0));
+        assert_eq!(vat_return.output_tax(), AmountEntity::new(7, 70));
+        assert_eq!(vat_return.input().len(), 1);
+        assert_eq!(vat_return.input()[0].turnover(), AmountEntity::new(100, 0));
+        assert_eq!(vat_return.input_tax(), AmountEntity::new(2, 50));
+        assert_eq!(vat_return.payable(), AmountEntity::new(5, 20));
+        Ok(())
+    }
+}
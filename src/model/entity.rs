@@ -1,15 +1,48 @@
 mod amount_entity;
+mod bank_statement;
+mod camt_statement;
+mod exchange_rate;
+mod fixed_point;
+mod journal_file;
 mod ledger_entity;
+mod percentage;
 mod transaction_entity;
 
 pub use amount_entity::AmountEntity;
+pub use bank_statement::read_csv_statement;
+pub use bank_statement::AmountSign;
+pub use bank_statement::BankStatement;
+pub use bank_statement::CsvMapping;
+pub use bank_statement::ImportSummary;
+pub use bank_statement::StatementBalance;
+pub use bank_statement::StatementEntry;
+pub use camt_statement::read_camt_statement;
+pub use exchange_rate::ExchangeRate;
+pub use journal_file::read_journal_file;
+pub use journal_file::JournalFile;
+pub use journal_file::JournalFileCost;
+pub use journal_file::JournalFileEntry;
+pub use journal_file::JournalFilePosting;
 pub use ledger_entity::AccountEntity;
+pub use ledger_entity::AccountGroups;
+pub use ledger_entity::AccountStart;
 pub use ledger_entity::AccountsReader;
+pub use ledger_entity::CurrencyEntity;
+pub use ledger_entity::FiscalYearEntity;
 pub use ledger_entity::LedgerEntity;
+pub use ledger_entity::RateEntity;
+pub use ledger_entity::RuleEntity;
+pub use ledger_entity::VatEntity;
+pub use percentage::Percentage;
+pub use transaction_entity::PostingLine;
+pub use transaction_entity::PostingSide;
+pub use transaction_entity::TaxCode;
 pub use transaction_entity::TransactionAccountReader;
 
+use crate::model::error::AccError;
 use serde::Deserialize;
 use serde::Serialize;
+use std::collections::HashMap;
 pub use transaction_entity::TransactionEntity;
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -19,7 +52,41 @@ pub struct Entity {
     pub transactions: Vec<TransactionEntity>,
 }
 
+impl Entity {
+    pub fn new(
+        name: String,
+        definition: LedgerEntity,
+        transactions: Vec<TransactionEntity>,
+    ) -> Self {
+        Entity {
+            name,
+            definition,
+            transactions,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Ledger of the following fiscal year, with the start amounts replaced by `starts` and without transactions.
+    /// A declared fiscal year is followed by the next twelve months.
+    pub fn carry_forward(
+        &self,
+        name: String,
+        starts: &HashMap<String, AccountStart>,
+    ) -> Result<Entity, AccError> {
+        Ok(Entity {
+            name,
+            definition: self.definition.carry_forward(starts)?,
+            transactions: vec![],
+        })
+    }
+}
+
 #[cfg(test)]
 pub use ledger_entity::MockLedgerEntity;
 #[cfg(test)]
+pub use ledger_entity::VatRateEntity;
+#[cfg(test)]
 pub use transaction_entity::MockTransactionEntity;
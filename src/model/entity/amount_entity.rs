@@ -1,56 +1,182 @@
-use serde::de::Visitor;
+use super::exchange_rate::ExchangeRate;
+use super::fixed_point::{self, DecimalVisitor};
+use crate::model::error::AccError;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::{Display, Formatter};
+use std::iter::Sum;
+use std::ops::{Add, Neg, Sub};
+use std::str::FromStr;
 
-#[derive(PartialEq, Debug, Clone)]
+/// Number of decimal places every amount is stored with.
+pub const SCALE: u32 = 2;
+const MINOR_PER_UNIT: i64 = 10_i64.pow(SCALE);
+
+/// Monetary amount stored as an exact number of minor units (e.g. Rappen).
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy, Default)]
 pub struct AmountEntity {
-    decimal_digits: u128,
-    dividend_digits: u32,
+    minor_units: i64,
 }
 
 impl AmountEntity {
-    pub fn new(decimal_digits: u128, dividend_digits: u32) -> Self {
-        AmountEntity {
-            decimal_digits,
-            dividend_digits,
-        }
+    /// Amount of `units` and `minor_units`, which take the sign of `units`, so `new(-1, 50)` is -1.50.
+    /// Amounts between -0.99 and -0.01 have no negative `units` and must be created with
+    /// [AmountEntity::from_minor_units]. Panics if the amount exceeds the range of amounts.
+    pub fn new(units: i64, minor_units: u32) -> Self {
+        let minor_units = i64::from(minor_units);
+        units
+            .checked_mul(MINOR_PER_UNIT)
+            .and_then(|major| {
+                if units < 0 {
+                    major.checked_sub(minor_units)
+                } else {
+                    major.checked_add(minor_units)
+                }
+            })
+            .filter(|total| *total != i64::MIN)
+            .map(AmountEntity::from_minor_units)
+            .expect("amount out of range")
+    }
+
+    pub fn from_minor_units(minor_units: i64) -> Self {
+        AmountEntity { minor_units }
+    }
+
+    pub fn zero() -> Self {
+        AmountEntity::default()
+    }
+
+    pub fn minor_units(&self) -> i64 {
+        self.minor_units
+    }
+
+    /// Sum of both amounts, [AccError::AmountOverflow] if it exceeds the range of amounts.
+    pub fn checked_add(self, other: Self) -> Result<Self, AccError> {
+        self.minor_units
+            .checked_add(other.minor_units)
+            .filter(|sum| *sum != i64::MIN)
+            .map(AmountEntity::from_minor_units)
+            .ok_or(AccError::AmountOverflow)
+    }
+
+    /// Difference of both amounts, [AccError::AmountOverflow] if it exceeds the range of amounts.
+    pub fn checked_sub(self, other: Self) -> Result<Self, AccError> {
+        self.checked_add(-other)
+    }
+
+    /// Sum of all amounts, [AccError::AmountOverflow] if an intermediate sum exceeds the range of amounts.
+    pub fn checked_sum(amounts: impl IntoIterator<Item = AmountEntity>) -> Result<Self, AccError> {
+        amounts
+            .into_iter()
+            .try_fold(AmountEntity::zero(), AmountEntity::checked_add)
+    }
+
+    /// The amount multiplied by `numerator / denominator`, rounded half away from zero to the nearest minor unit.
+    /// [AccError::AmountOverflow] if `denominator` is zero or the result exceeds the range of amounts.
+    pub fn scale(self, numerator: i64, denominator: i64) -> Result<Self, AccError> {
+        divide_rounded(
+            i128::from(self.minor_units) * i128::from(numerator),
+            i128::from(denominator),
+        )
+        .and_then(|scaled| i64::try_from(scaled).ok())
+        .filter(|scaled| *scaled != i64::MIN)
+        .map(AmountEntity::from_minor_units)
+        .ok_or(AccError::AmountOverflow)
+    }
+
+    /// The share `part / whole` of the amount, rounded half away from zero to the nearest minor unit.
+    pub fn share(self, part: Self, whole: Self) -> Result<Self, AccError> {
+        self.scale(part.minor_units, whole.minor_units)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.minor_units == 0
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.minor_units < 0
+    }
+
+    pub fn abs(&self) -> Self {
+        AmountEntity::from_minor_units(self.minor_units.abs())
+    }
+
+    /// Multiplies the amount by the exchange rate, rounded half away from zero to the nearest minor unit.
+    pub fn convert(self, rate: ExchangeRate) -> Result<Self, AccError> {
+        self.scale(rate.micro_units(), ExchangeRate::one().micro_units())
     }
 }
 
-impl Serialize for AmountEntity {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        let v = format!("{}.{}", self.decimal_digits, self.dividend_digits);
-        let parsed: f64 = v.parse().map_err(serde::ser::Error::custom)?;
-        serializer.serialize_f64(parsed)
+/// Quotient rounded half away from zero, `None` if `denominator` is zero.
+fn divide_rounded(numerator: i128, denominator: i128) -> Option<i128> {
+    let quotient = numerator.checked_div(denominator)?;
+    let remainder = numerator % denominator;
+    if remainder.unsigned_abs() * 2 >= denominator.unsigned_abs() {
+        Some(quotient + numerator.signum() * denominator.signum())
+    } else {
+        Some(quotient)
+    }
+}
+
+impl Display for AmountEntity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.pad(&fixed_point::format(
+            self.minor_units,
+            SCALE,
+            SCALE as usize,
+        ))
+    }
+}
+
+impl FromStr for AmountEntity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        fixed_point::parse(s, SCALE).map(AmountEntity::from_minor_units)
+    }
+}
+
+/// The range of amounts is symmetric, `i64::MIN` minor units are never produced, so negating cannot overflow.
+impl Neg for AmountEntity {
+    type Output = AmountEntity;
+
+    fn neg(self) -> Self::Output {
+        AmountEntity::from_minor_units(-self.minor_units)
     }
 }
 
-struct AmountVisitor;
+/// Panics if the sum exceeds the range of amounts, use [AmountEntity::checked_add] where it can.
+impl Add for AmountEntity {
+    type Output = AmountEntity;
 
-impl<'de> Visitor<'de> for AmountVisitor {
-    type Value = AmountEntity;
+    fn add(self, other: Self) -> Self::Output {
+        self.checked_add(other)
+            .expect("attempt to add with overflow")
+    }
+}
+
+/// Panics if the difference exceeds the range of amounts, use [AmountEntity::checked_sub] where it can.
+impl Sub for AmountEntity {
+    type Output = AmountEntity;
+
+    fn sub(self, other: Self) -> Self::Output {
+        self.checked_sub(other)
+            .expect("attempt to subtract with overflow")
+    }
+}
 
-    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-        formatter.write_str("an amount with decimal places like 10.00")
+/// Panics if an intermediate sum exceeds the range of amounts, use [AmountEntity::checked_sum] where it can.
+impl Sum for AmountEntity {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(AmountEntity::zero(), Add::add)
     }
+}
 
-    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+impl Serialize for AmountEntity {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
-        E: serde::de::Error,
+        S: Serializer,
     {
-        let parts: Vec<&str> = v.split(".").collect();
-        let pre_decimal: u128 = parts[0].parse().map_err(serde::de::Error::custom)?;
-        let decimal_places: u32 = if parts.len() > 1 && parts[1].trim_matches('0').len() > 0 {
-            parts[1]
-                .trim_matches('0')
-                .parse()
-                .map_err(serde::de::Error::custom)?
-        } else {
-            0
-        };
-        Ok(AmountEntity::new(pre_decimal, decimal_places))
+        fixed_point::serialize(self.minor_units, &self.to_string(), serializer)
     }
 }
 
@@ -59,7 +185,9 @@ impl<'de> Deserialize<'de> for AmountEntity {
     where
         D: Deserializer<'de>,
     {
-        deserializer.deserialize_str(AmountVisitor)
+        deserializer.deserialize_str(DecimalVisitor::new(
+            "an amount with decimal places like 10.00",
+        ))
     }
 }
 
@@ -101,6 +229,112 @@ mod tests {
         assert_eq!(amount, super::AmountEntity::new(11, 0));
         Ok(())
     }
+
+    #[test]
+    fn test_amount_keeps_leading_zero_in_decimal_places() -> Result<(), serde_yaml::Error> {
+        let amount: super::AmountEntity = serde_yaml::from_str(r#"10.05"#)?;
+        let other: super::AmountEntity = serde_yaml::from_str(r#"10.5"#)?;
+        assert_eq!(amount, super::AmountEntity::new(10, 5));
+        assert_eq!(other, super::AmountEntity::new(10, 50));
+        assert!(amount < other);
+        Ok(())
+    }
+
+    #[test]
+    fn test_negative_amount() -> Result<(), serde_yaml::Error> {
+        let amount: super::AmountEntity = serde_yaml::from_str(r#"-3.20"#)?;
+        assert_eq!(amount, super::AmountEntity::from_minor_units(-320));
+        Ok(())
+    }
+
+    #[test]
+    fn test_too_many_decimal_places() {
+        let amount: Result<super::AmountEntity, serde_yaml::Error> =
+            serde_yaml::from_str(r#"10.005"#);
+        assert!(amount.is_err());
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a = super::AmountEntity::new(10, 5);
+        let b = super::AmountEntity::new(0, 95);
+        assert_eq!(a.checked_add(b).unwrap(), super::AmountEntity::new(11, 0));
+        assert_eq!(a.checked_sub(b).unwrap(), super::AmountEntity::new(9, 10));
+        assert_eq!(b.checked_sub(a).unwrap(), super::AmountEntity::new(-9, 10));
+        assert_eq!(-a, super::AmountEntity::from_minor_units(-1005));
+        assert_eq!(
+            super::AmountEntity::checked_sum(vec![a, b, a]).unwrap(),
+            super::AmountEntity::new(21, 5)
+        );
+    }
+
+    #[test]
+    fn test_operators() {
+        let a = super::AmountEntity::new(10, 5);
+        let b = super::AmountEntity::from_minor_units(-95);
+        assert_eq!(a + b, super::AmountEntity::new(9, 10));
+        assert_eq!(b - a, super::AmountEntity::new(-11, 0));
+        assert_eq!(
+            vec![a, b, a].into_iter().sum::<super::AmountEntity>(),
+            super::AmountEntity::new(19, 15)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "attempt to add with overflow")]
+    fn test_add_overflow() {
+        let _ = super::AmountEntity::from_minor_units(i64::MAX)
+            + super::AmountEntity::from_minor_units(1);
+    }
+
+    #[test]
+    #[should_panic(expected = "amount out of range")]
+    fn test_new_out_of_range() {
+        super::AmountEntity::new(i64::MAX / 10, 0);
+    }
+
+    #[test]
+    fn test_arithmetic_overflow() {
+        let max = super::AmountEntity::from_minor_units(i64::MAX);
+        let one = super::AmountEntity::from_minor_units(1);
+        assert!(matches!(
+            max.checked_add(one),
+            Err(super::AccError::AmountOverflow)
+        ));
+        assert!(matches!(
+            (-max).checked_sub(one),
+            Err(super::AccError::AmountOverflow)
+        ));
+        assert!(super::AmountEntity::checked_sum(vec![max, one, -one]).is_err());
+        assert_eq!(max.checked_sub(one).unwrap().minor_units(), i64::MAX - 1);
+    }
+
+    #[test]
+    fn test_share() {
+        let amount = super::AmountEntity::new(100, 0);
+        let share = |part: i64, whole: i64| {
+            amount.share(
+                super::AmountEntity::from_minor_units(part),
+                super::AmountEntity::from_minor_units(whole),
+            )
+        };
+        assert_eq!(share(1, 3).unwrap(), super::AmountEntity::new(33, 33));
+        assert_eq!(share(2, 3).unwrap(), super::AmountEntity::new(66, 67));
+        assert_eq!(share(-1, 8).unwrap(), super::AmountEntity::new(-12, 50));
+        assert_eq!(share(-1, 3).unwrap(), super::AmountEntity::new(-33, 33));
+        assert!(share(1, 0).is_err());
+        assert!(share(i64::MAX, 1).is_err());
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(super::AmountEntity::new(10, 5).to_string(), "10.05");
+        assert_eq!(super::AmountEntity::new(-1, 50).to_string(), "-1.50");
+        assert_eq!(
+            super::AmountEntity::from_minor_units(-5).to_string(),
+            "-0.05"
+        );
+    }
 }
 
 #[cfg(test)]
@@ -118,4 +352,47 @@ mod test_serialize_amount {
         assert_eq!(amount, "999.99\n");
         Ok(())
     }
+
+    #[test]
+    fn test_round_trip() -> Result<(), serde_yaml::Error> {
+        for raw in [
+            "0.01",
+            "10.05",
+            "-250.10",
+            "1234567890123.45",
+            "987654321098765.43",
+        ] {
+            let amount: super::AmountEntity = serde_yaml::from_str(raw)?;
+            let serialized = serde_yaml::to_string(&amount)?;
+            let parsed: super::AmountEntity = serde_yaml::from_str(&serialized)?;
+            assert_eq!(parsed, amount);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_convert() {
+        let rate = |micro_units: i64| super::ExchangeRate::from_micro_units(micro_units);
+        assert_eq!(
+            super::AmountEntity::new(100, 0)
+                .convert(rate(985_600))
+                .unwrap(),
+            super::AmountEntity::new(98, 56)
+        );
+        assert_eq!(
+            super::AmountEntity::new(-10, 5)
+                .convert(rate(1_100_000))
+                .unwrap(),
+            super::AmountEntity::new(-11, 6)
+        );
+        assert_eq!(
+            super::AmountEntity::new(0, 1)
+                .convert(rate(500_000))
+                .unwrap(),
+            super::AmountEntity::new(0, 1)
+        );
+        assert!(super::AmountEntity::from_minor_units(i64::MAX)
+            .convert(rate(1_100_000))
+            .is_err());
+    }
 }
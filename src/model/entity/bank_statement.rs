@@ -0,0 +1,235 @@
+use super::amount_entity::AmountEntity;
+use super::transaction_entity::TransactionEntity;
+use crate::model::error::{AccError, Result};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+
+/// Whether positive amounts in the statement are money coming into or going out of the bank account.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum AmountSign {
+    #[default]
+    InflowPositive,
+    OutflowPositive,
+}
+
+/// Columns of a bank CSV export, referenced by their header.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct CsvMapping {
+    pub delimiter: u8,
+    pub date_column: String,
+    /// Format of the dates as understood by [NaiveDate::parse_from_str], e.g. `%d.%m.%Y`.
+    pub date_format: String,
+    pub amount_column: String,
+    pub amount_sign: AmountSign,
+    /// Character separating the decimal places, thousands separators `'` and `,` or `.` are ignored.
+    pub decimal_separator: char,
+    pub description_column: String,
+}
+
+impl Default for CsvMapping {
+    fn default() -> Self {
+        CsvMapping {
+            delimiter: b',',
+            date_column: "Date".to_string(),
+            date_format: "%Y-%m-%d".to_string(),
+            amount_column: "Amount".to_string(),
+            amount_sign: AmountSign::InflowPositive,
+            decimal_separator: '.',
+            description_column: "Description".to_string(),
+        }
+    }
+}
+
+/// Booking on the bank account as read from a statement, positive amounts are inflows.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Default)]
+pub struct StatementEntry {
+    pub date: NaiveDate,
+    pub amount: AmountEntity,
+    /// Currency of the amount if the statement states one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+    #[serde(default)]
+    pub description: String,
+    /// Reference of the bank identifying the entry.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+    /// Name of the other party of a payment.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub counterparty: Option<String>,
+}
+
+/// Balance of the bank account at the end of `date`.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy)]
+pub struct StatementBalance {
+    pub date: NaiveDate,
+    pub amount: AmountEntity,
+}
+
+/// Bank statement with the balances it states and its entries.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Default)]
+pub struct BankStatement {
+    #[serde(default)]
+    pub balances: Vec<StatementBalance>,
+    pub entries: Vec<StatementEntry>,
+}
+
+impl BankStatement {
+    /// Latest balance of the statement, of balances with the same date the last one.
+    pub fn closing(&self) -> Option<StatementBalance> {
+        self.balances
+            .iter()
+            .max_by_key(|balance| balance.date)
+            .copied()
+    }
+}
+
+impl StatementEntry {
+    /// Inflows debit the bank account and credit the counter-account, outflows the other way round.
+    pub fn to_transaction(&self, bank_account: &str, counter_account: &str) -> TransactionEntity {
+        let (deb, cred) = if self.amount.is_negative() {
+            (counter_account, bank_account)
+        } else {
+            (bank_account, counter_account)
+        };
+        let transaction = TransactionEntity::new(
+            self.description.clone(),
+            self.amount.abs(),
+            deb.to_string(),
+            cred.to_string(),
+            self.date,
+        );
+        match &self.reference {
+            Some(reference) => transaction.with_reference(reference.clone()),
+            None => transaction,
+        }
+    }
+}
+
+/// Outcome of appending a statement to the ledger.
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct ImportSummary {
+    pub imported: usize,
+    /// Entries whose reference was imported before.
+    pub skipped: usize,
+    /// Imported entries no rule matched, they are booked onto the placeholder counter-account.
+    pub unmatched: Vec<StatementEntry>,
+}
+
+/// Reads the rows of a bank CSV export, rows without an amount are skipped.
+pub fn read_csv_statement(reader: impl Read, mapping: &CsvMapping) -> Result<Vec<StatementEntry>> {
+    let mut csv_reader = csv::ReaderBuilder::new()
+        .delimiter(mapping.delimiter)
+        .flexible(true)
+        .from_reader(reader);
+    let headers = csv_reader.headers()?.clone();
+    let column = |name: &str| {
+        headers
+            .iter()
+            .position(|header| header.trim() == name)
+            .ok_or_else(|| AccError::Import {
+                line: Some(1),
+                message: format!("column {name} not found"),
+            })
+    };
+    let date_column = column(&mapping.date_column)?;
+    let amount_column = column(&mapping.amount_column)?;
+    let description_column = column(&mapping.description_column)?;
+    let mut entries = vec![];
+    for record in csv_reader.records() {
+        let record = record?;
+        let line = record.position().map(|position| position.line() as usize);
+        let field = |index: usize| record.get(index).unwrap_or("").trim();
+        let error = |message: String| AccError::Import { line, message };
+        if field(amount_column).is_empty() {
+            continue;
+        }
+        let date = NaiveDate::parse_from_str(field(date_column), &mapping.date_format)
+            .map_err(|err| error(format!("invalid date {}: {err}", field(date_column))))?;
+        let amount =
+            parse_amount(field(amount_column), mapping.decimal_separator).map_err(error)?;
+        entries.push(StatementEntry {
+            date,
+            amount: match mapping.amount_sign {
+                AmountSign::InflowPositive => amount,
+                AmountSign::OutflowPositive => -amount,
+            },
+            description: field(description_column).to_string(),
+            ..StatementEntry::default()
+        });
+    }
+    Ok(entries)
+}
+
+fn parse_amount(value: &str, decimal_separator: char) -> std::result::Result<AmountEntity, String> {
+    let normalized: String = value
+        .chars()
+        .filter(|c| {
+            !(c.is_whitespace() || *c == '\'' || matches!(c, ',' | '.') && *c != decimal_separator)
+        })
+        .map(|c| if c == decimal_separator { '.' } else { c })
+        .collect();
+    normalized.parse()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_csv_statement, AmountSign, CsvMapping, StatementEntry};
+    use crate::model::entity::transaction_entity::TransactionAccountReader;
+    use crate::model::entity::AmountEntity;
+    use crate::model::error::{AccError, Result};
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_read_csv_statement() -> Result<()> {
+        let csv = "Buchungsdatum;Text;Betrag;Saldo\n\
+                   31.01.2022;Kartenzahlung Migros;1'234,50;5000\n\
+                   01.02.2022;Gutschrift Kunde;-200,00;4800\n\
+                   02.02.2022;Saldovortrag;;4800\n";
+        let mapping = CsvMapping {
+            delimiter: b';',
+            date_column: "Buchungsdatum".to_string(),
+            date_format: "%d.%m.%Y".to_string(),
+            amount_column: "Betrag".to_string(),
+            amount_sign: AmountSign::OutflowPositive,
+            decimal_separator: ',',
+            description_column: "Text".to_string(),
+        };
+
+        let entries = read_csv_statement(csv.as_bytes(), &mapping)?;
+
+        assert_eq!(
+            entries,
+            vec![
+                StatementEntry {
+                    date: NaiveDate::from_ymd_opt(2022, 1, 31).unwrap(),
+                    amount: AmountEntity::new(-1234, 50),
+                    description: "Kartenzahlung Migros".to_string(),
+                    ..StatementEntry::default()
+                },
+                StatementEntry {
+                    date: NaiveDate::from_ymd_opt(2022, 2, 1).unwrap(),
+                    amount: AmountEntity::new(200, 0),
+                    description: "Gutschrift Kunde".to_string(),
+                    ..StatementEntry::default()
+                },
+            ]
+        );
+        let transaction = entries[0].to_transaction("Bank", "Unassigned");
+        let lines = transaction.read_lines();
+        assert_eq!(lines[0].account, "Unassigned");
+        assert_eq!(lines[1].account, "Bank");
+        assert_eq!(lines[1].amount, AmountEntity::new(1234, 50));
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_csv_row() {
+        let csv = "Date,Description,Amount\n2022-13-01,Kauf,10.00\n";
+
+        assert!(matches!(
+            read_csv_statement(csv.as_bytes(), &CsvMapping::default()),
+            Err(AccError::Import { line: Some(2), .. })
+        ));
+    }
+}
@@ -0,0 +1,204 @@
+use super::amount_entity::AmountEntity;
+use super::bank_statement::{BankStatement, StatementBalance, StatementEntry};
+use crate::model::error::{AccError, Result};
+use chrono::NaiveDate;
+use roxmltree::{Document, Node};
+use std::io::Read;
+
+/// Reads the balances (`Bal`) and entries (`Ntry`) of an ISO 20022 camt.053 bank statement.
+/// The unstructured remittance information becomes the description, the reference of the bank
+/// (`AcctSvcrRef`) identifies the entry.
+pub fn read_camt_statement(mut reader: impl Read) -> Result<BankStatement> {
+    let mut xml = String::new();
+    reader.read_to_string(&mut xml)?;
+    let document = Document::parse(&xml).map_err(|err| AccError::Import {
+        line: Some(err.pos().row as usize),
+        message: err.to_string(),
+    })?;
+    let located = |node: Node, message: String| AccError::Import {
+        line: Some(document.text_pos_at(node.range().start).row as usize),
+        message,
+    };
+    let balances = document
+        .descendants()
+        .filter(|node| node.has_tag_name("Bal"))
+        .map(|balance| read_balance(balance).map_err(|message| located(balance, message)))
+        .collect::<Result<Vec<StatementBalance>>>()?;
+    let entries = document
+        .descendants()
+        .filter(|node| node.has_tag_name("Ntry"))
+        .map(|entry| read_entry(entry).map_err(|message| located(entry, message)))
+        .collect::<Result<Vec<StatementEntry>>>()?;
+    Ok(BankStatement { balances, entries })
+}
+
+fn read_balance(balance: Node) -> std::result::Result<StatementBalance, String> {
+    let (amount, _) = read_amount(balance)?;
+    let date = child(balance, "Dt")
+        .ok_or_else(|| "balance without date".to_string())
+        .and_then(read_date)?;
+    Ok(StatementBalance { date, amount })
+}
+
+fn read_entry(entry: Node) -> std::result::Result<StatementEntry, String> {
+    let (amount, currency) = read_amount(entry)?;
+    let credit = !amount.is_negative();
+    let date = child(entry, "BookgDt")
+        .ok_or_else(|| "entry without booking date".to_string())
+        .and_then(read_date)?;
+    let remittance: Vec<&str> = entry
+        .descendants()
+        .filter(|node| node.has_tag_name("Ustrd"))
+        .map(text)
+        .filter(|text| !text.is_empty())
+        .collect();
+    let description = if remittance.is_empty() {
+        child(entry, "AddtlNtryInf")
+            .map(text)
+            .unwrap_or_default()
+            .to_string()
+    } else {
+        remittance.join(" ")
+    };
+    let reference = child(entry, "AcctSvcrRef")
+        .or_else(|| {
+            entry
+                .descendants()
+                .filter(|node| node.has_tag_name("Refs"))
+                .find_map(|refs| child(refs, "AcctSvcrRef"))
+        })
+        .map(|reference| text(reference).to_string());
+    let counterparty = entry
+        .descendants()
+        .find(|node| node.has_tag_name(if credit { "Dbtr" } else { "Cdtr" }))
+        .and_then(|party| party.descendants().find(|node| node.has_tag_name("Nm")))
+        .map(|name| text(name).to_string());
+    Ok(StatementEntry {
+        date,
+        amount,
+        currency,
+        description,
+        reference,
+        counterparty,
+    })
+}
+
+/// Amount with the sign of the credit/debit indicator and its currency.
+fn read_amount(node: Node) -> std::result::Result<(AmountEntity, Option<String>), String> {
+    let amount_node = child(node, "Amt").ok_or("amount missing")?;
+    let amount: AmountEntity = text(amount_node).parse()?;
+    let amount = match child(node, "CdtDbtInd").map(text) {
+        Some("CRDT") => amount,
+        Some("DBIT") => -amount,
+        other => return Err(format!("unknown credit/debit indicator {other:?}")),
+    };
+    Ok((amount, amount_node.attribute("Ccy").map(str::to_string)))
+}
+
+/// Date of a date choice element holding either `Dt` or `DtTm`.
+fn read_date(node: Node) -> std::result::Result<NaiveDate, String> {
+    let date = child(node, "Dt")
+        .or_else(|| child(node, "DtTm"))
+        .ok_or("date missing")?;
+    let date_text = text(date);
+    NaiveDate::parse_from_str(date_text.get(..10).unwrap_or(date_text), "%Y-%m-%d")
+        .map_err(|err| format!("invalid date {date_text}: {err}"))
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|child| child.has_tag_name(name))
+}
+
+fn text<'a>(node: Node<'a, '_>) -> &'a str {
+    node.text().unwrap_or_default().trim()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::read_camt_statement;
+    use crate::model::entity::{AmountEntity, StatementBalance, StatementEntry};
+    use crate::model::error::{AccError, Result};
+    use chrono::NaiveDate;
+
+    const STATEMENT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Document xmlns="urn:iso:std:iso:20022:tech:xsd:camt.053.001.04">
+  <BkToCstmrStmt>
+    <Stmt>
+      <Bal>
+        <Tp><CdOrPrtry><Cd>OPBD</Cd></CdOrPrtry></Tp>
+        <Amt Ccy="CHF">200.00</Amt>
+        <CdtDbtInd>DBIT</CdtDbtInd>
+        <Dt><Dt>2022-02-28</Dt></Dt>
+      </Bal>
+      <Bal>
+        <Tp><CdOrPrtry><Cd>CLBD</Cd></CdOrPrtry></Tp>
+        <Amt Ccy="CHF">1219.50</Amt>
+        <CdtDbtInd>CRDT</CdtDbtInd>
+        <Dt><Dt>2022-03-02</Dt></Dt>
+      </Bal>
+      <Ntry>
+        <Amt Ccy="CHF">1500.00</Amt>
+        <CdtDbtInd>CRDT</CdtDbtInd>
+        <BookgDt><Dt>2022-03-01</Dt></BookgDt>
+        <AcctSvcrRef>REF-1</AcctSvcrRef>
+        <NtryDtls><TxDtls>
+          <RltdPties><Dbtr><Nm>Muster AG</Nm></Dbtr></RltdPties>
+          <RmtInf><Ustrd>Rechnung 42</Ustrd></RmtInf>
+        </TxDtls></NtryDtls>
+      </Ntry>
+      <Ntry>
+        <Amt Ccy="CHF">80.50</Amt>
+        <CdtDbtInd>DBIT</CdtDbtInd>
+        <BookgDt><DtTm>2022-03-02T10:15:00</DtTm></BookgDt>
+        <AddtlNtryInf>Kontoführung</AddtlNtryInf>
+      </Ntry>
+    </Stmt>
+  </BkToCstmrStmt>
+</Document>"#;
+
+    #[test]
+    fn test_read_camt_statement() -> Result<()> {
+        let statement = read_camt_statement(STATEMENT.as_bytes())?;
+
+        assert_eq!(statement.balances[0].amount, AmountEntity::new(-200, 0));
+        assert_eq!(
+            statement.closing(),
+            Some(StatementBalance {
+                date: NaiveDate::from_ymd_opt(2022, 3, 2).unwrap(),
+                amount: AmountEntity::new(1219, 50),
+            })
+        );
+        assert_eq!(
+            statement.entries,
+            vec![
+                StatementEntry {
+                    date: NaiveDate::from_ymd_opt(2022, 3, 1).unwrap(),
+                    amount: AmountEntity::new(1500, 0),
+                    currency: Some("CHF".to_string()),
+                    description: "Rechnung 42".to_string(),
+                    reference: Some("REF-1".to_string()),
+                    counterparty: Some("Muster AG".to_string()),
+                },
+                StatementEntry {
+                    date: NaiveDate::from_ymd_opt(2022, 3, 2).unwrap(),
+                    amount: AmountEntity::new(-80, 50),
+                    currency: Some("CHF".to_string()),
+                    description: "Kontoführung".to_string(),
+                    reference: None,
+                    counterparty: None,
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_entry_without_indicator() {
+        let statement = "<Document>\n<Ntry><Amt>1.00</Amt></Ntry>\n</Document>";
+
+        assert!(matches!(
+            read_camt_statement(statement.as_bytes()),
+            Err(AccError::Import { line: Some(2), .. })
+        ));
+    }
+}
@@ -0,0 +1,83 @@
+use super::fixed_point::{self, DecimalVisitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Number of decimal places every exchange rate is stored with.
+const SCALE: u32 = 6;
+const MICRO_PER_UNIT: i64 = 10_i64.pow(SCALE);
+
+/// Positive exchange rate stored as an exact number of millionths.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+pub struct ExchangeRate {
+    micro_units: i64,
+}
+
+impl ExchangeRate {
+    pub fn from_micro_units(micro_units: i64) -> Self {
+        ExchangeRate { micro_units }
+    }
+
+    /// Rate of the base currency.
+    pub fn one() -> Self {
+        ExchangeRate::from_micro_units(MICRO_PER_UNIT)
+    }
+
+    pub fn micro_units(&self) -> i64 {
+        self.micro_units
+    }
+}
+
+impl Display for ExchangeRate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.pad(&fixed_point::format(self.micro_units, SCALE, 0))
+    }
+}
+
+impl FromStr for ExchangeRate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let micro_units = fixed_point::parse(s, SCALE)?;
+        if micro_units <= 0 {
+            return Err(format!("{s} is not a valid exchange rate"));
+        }
+        Ok(ExchangeRate::from_micro_units(micro_units))
+    }
+}
+
+impl Serialize for ExchangeRate {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        fixed_point::serialize(self.micro_units, &self.to_string(), serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ExchangeRate {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(DecimalVisitor::new(
+            "a positive exchange rate with at most 6 decimal places like 0.95",
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    #[test]
+    fn test_exchange_rate() -> Result<(), serde_yaml::Error> {
+        let rate: super::ExchangeRate = serde_yaml::from_str("0.9312")?;
+        assert_eq!(rate.micro_units(), 931_200);
+        assert_eq!(rate.to_string(), "0.9312");
+        assert_eq!(serde_yaml::to_string(&rate)?, "0.9312\n");
+        assert_eq!(super::ExchangeRate::one().to_string(), "1");
+        assert!(serde_yaml::from_str::<super::ExchangeRate>("0").is_err());
+        assert!(serde_yaml::from_str::<super::ExchangeRate>("1.0000001").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,149 @@
+use serde::de::Visitor;
+use serde::Serializer;
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+/// Largest absolute number of units which is written as a plain yaml number.
+/// Up to 15 significant digits survive the shortest f64 representation unchanged.
+const MAX_EXACT_FLOAT: i64 = 999_999_999_999_999;
+
+/// Parses a decimal number like `-10.05` into a whole number of `10^-scale` units.
+/// More significant decimal places than `scale` cannot be represented exactly and are rejected.
+pub(super) fn parse(s: &str, scale: u32) -> Result<i64, String> {
+    let trimmed = s.trim();
+    let (negative, digits) = match trimmed.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, trimmed.strip_prefix('+').unwrap_or(trimmed)),
+    };
+    let (pre_decimal, decimal_places) = digits.split_once('.').unwrap_or((digits, ""));
+    let is_digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+    if pre_decimal.is_empty() || !is_digits(pre_decimal) || !is_digits(decimal_places) {
+        return Err(format!("{s} is not a valid number"));
+    }
+    let significant_places = decimal_places.trim_end_matches('0');
+    if significant_places.len() > scale as usize {
+        return Err(format!(
+            "{s} has more than {scale} decimal places and cannot be represented exactly"
+        ));
+    }
+    let units: i64 = pre_decimal
+        .parse()
+        .map_err(|_| format!("{s} is out of range"))?;
+    let fraction: i64 = if scale == 0 {
+        0
+    } else {
+        format!("{significant_places:0<width$}", width = scale as usize)
+            .parse()
+            .map_err(|_| format!("{s} is not a valid number"))?
+    };
+    let total = units
+        .checked_mul(10_i64.pow(scale))
+        .and_then(|v| v.checked_add(fraction))
+        .ok_or_else(|| format!("{s} is out of range"))?;
+    Ok(if negative { -total } else { total })
+}
+
+/// Formats a whole number of `10^-scale` units as decimal number with at least `min_places`
+/// decimal places, further decimal places are only written if they are not zero.
+pub(super) fn format(value: i64, scale: u32, min_places: usize) -> String {
+    let sign = if value < 0 { "-" } else { "" };
+    let abs = value.unsigned_abs();
+    let unit = 10_u64.pow(scale);
+    let fraction = format!("{:0width$}", abs % unit, width = scale as usize);
+    let significant = fraction.trim_end_matches('0');
+    let places = significant.len().max(min_places.min(scale as usize));
+    if places == 0 {
+        format!("{sign}{}", abs / unit)
+    } else {
+        format!("{sign}{}.{}", abs / unit, &fraction[..places])
+    }
+}
+
+/// Writes the number as plain yaml number if it survives the conversion to f64, otherwise as string.
+pub(super) fn serialize<S>(value: i64, formatted: &str, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    if value.abs() <= MAX_EXACT_FLOAT {
+        let parsed: f64 = formatted.parse().map_err(serde::ser::Error::custom)?;
+        serializer.serialize_f64(parsed)
+    } else {
+        serializer.serialize_str(formatted)
+    }
+}
+
+/// Reads a decimal number from a yaml number or string without passing through f64 arithmetic.
+pub(super) struct DecimalVisitor<T> {
+    expecting: &'static str,
+    value: PhantomData<T>,
+}
+
+impl<T> DecimalVisitor<T> {
+    pub(super) fn new(expecting: &'static str) -> Self {
+        DecimalVisitor {
+            expecting,
+            value: PhantomData,
+        }
+    }
+}
+
+impl<'de, T> Visitor<'de> for DecimalVisitor<T>
+where
+    T: FromStr<Err = String>,
+{
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str(self.expecting)
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        v.parse().map_err(serde::de::Error::custom)
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        self.visit_str(&v.to_string())
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        self.visit_str(&v.to_string())
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        self.visit_str(&v.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(super::parse("10.05", 2), Ok(1005));
+        assert_eq!(super::parse("-0.5", 6), Ok(-500_000));
+        assert_eq!(super::parse("7", 0), Ok(7));
+        assert!(super::parse("0.0000001", 6).is_err());
+        assert!(super::parse("1e3", 2).is_err());
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(super::format(1005, 2, 2), "10.05");
+        assert_eq!(super::format(-50, 2, 2), "-0.50");
+        assert_eq!(super::format(950_000, 6, 1), "0.95");
+        assert_eq!(super::format(1_000_000, 6, 0), "1");
+        assert_eq!(super::format(810, 2, 1), "8.1");
+    }
+}
@@ -0,0 +1,497 @@
+use super::amount_entity::AmountEntity;
+use super::exchange_rate::ExchangeRate;
+use crate::model::error::{AccError, EntryError, EntryProblem, Result};
+use chrono::NaiveDate;
+use std::io::Read;
+
+/// Posting of a journal entry, positive amounts are debits and negative amounts credits.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct JournalFilePosting {
+    pub account: String,
+    /// Amount of the posting, `None` if it is elided and balances the entry.
+    pub amount: Option<AmountEntity>,
+    /// Commodity of the amount as written, e.g. `CHF` or `$`.
+    pub commodity: Option<String>,
+    /// Virtual postings in parentheses or brackets do not have to balance.
+    pub virtual_posting: bool,
+    /// Conversion of the amount into another commodity, if any.
+    pub cost: Option<JournalFileCost>,
+    /// Tagged `exchange-difference:`, i.e. an exchange difference realized by the posting of another
+    /// foreign currency amount of the entry.
+    pub exchange_difference: bool,
+}
+
+/// Cost a posting amount is converted with.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum JournalFileCost {
+    /// Price per unit, written with `@`.
+    Unit,
+    /// Total value of the amount without sign, written with `@@`.
+    Total {
+        amount: AmountEntity,
+        commodity: Option<String>,
+    },
+}
+
+/// Dated entry of a journal, `line` is the line of its header starting at 1.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct JournalFileEntry {
+    pub line: usize,
+    pub date: NaiveDate,
+    /// Marked with `*`, i.e. matched with a bank statement.
+    pub cleared: bool,
+    pub description: String,
+    /// The `reference:` tag of the entry or its code in parentheses.
+    pub reference: Option<String>,
+    pub postings: Vec<JournalFilePosting>,
+}
+
+/// Account declared with an `account` directive.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct JournalFileAccount {
+    pub line: usize,
+    pub name: String,
+    /// Tagged `fx-account:`, the account realized exchange differences are booked onto.
+    pub fx_account: bool,
+}
+
+/// Price of a commodity declared with a `P` directive, e.g. `P 2022-06-01 EUR 0.95 CHF`.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct JournalFilePrice {
+    pub line: usize,
+    pub date: NaiveDate,
+    pub commodity: String,
+    pub rate: ExchangeRate,
+    /// Commodity the price is given in.
+    pub currency: String,
+}
+
+/// Entries, account declarations and prices of a ledger-cli or hledger journal.
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct JournalFile {
+    pub accounts: Vec<JournalFileAccount>,
+    pub prices: Vec<JournalFilePrice>,
+    pub entries: Vec<JournalFileEntry>,
+}
+
+/// Directives changing account names or the read files, which cannot be followed.
+const UNSUPPORTED_DIRECTIVES: [&str; 3] = ["include", "alias", "apply"];
+
+/// Reads the entries of a ledger-cli or hledger journal. Commodity and other directives are skipped,
+/// every line which cannot be read is reported.
+pub fn read_journal_file(mut reader: impl Read) -> Result<JournalFile> {
+    let mut content = String::new();
+    reader.read_to_string(&mut content)?;
+    let mut journal = JournalFile::default();
+    let mut errors = vec![];
+    let mut in_entry = false;
+    let mut in_comment_block = false;
+    for (index, raw_line) in content.lines().enumerate() {
+        let line = index + 1;
+        let error = |problem: EntryProblem| EntryError {
+            line,
+            description: raw_line.trim().to_string(),
+            problem,
+        };
+        if in_comment_block {
+            in_comment_block = raw_line.trim() != "end comment";
+            continue;
+        }
+        if raw_line.trim().is_empty() {
+            in_entry = false;
+            continue;
+        }
+        if raw_line.starts_with(char::is_whitespace) {
+            let trimmed = raw_line.trim();
+            match journal.entries.last_mut() {
+                Some(entry) if in_entry => {
+                    if let Some(comment) = trimmed.strip_prefix(';') {
+                        if let Some(reference) = reference_tag(comment) {
+                            entry.reference = Some(reference);
+                        }
+                    } else {
+                        match read_posting(trimmed) {
+                            Ok(posting) => entry.postings.push(posting),
+                            Err(message) => {
+                                errors.push(error(EntryProblem::InvalidSyntax(message)))
+                            }
+                        }
+                    }
+                }
+                // Sub-directives of account or commodity directives and comments.
+                _ => {}
+            }
+            continue;
+        }
+        in_entry = false;
+        let first = raw_line.chars().next().unwrap_or_default();
+        let keyword = raw_line.split_whitespace().next().unwrap_or_default();
+        if first.is_ascii_digit() {
+            match read_header(raw_line, line) {
+                Ok(entry) => {
+                    journal.entries.push(entry);
+                    in_entry = true;
+                }
+                Err(message) => errors.push(error(EntryProblem::InvalidSyntax(message))),
+            }
+        } else if [';', '#', '*', '%', '|'].contains(&first) {
+            continue;
+        } else if keyword == "comment" {
+            in_comment_block = true;
+        } else if keyword == "account" {
+            let declaration = &raw_line["account".len()..];
+            journal.accounts.push(JournalFileAccount {
+                line,
+                name: strip_comment(declaration).to_string(),
+                fx_account: declaration
+                    .split_once(';')
+                    .is_some_and(|(_, comment)| has_tag(comment, "fx-account")),
+            });
+        } else if keyword == "P" {
+            match read_price(&raw_line[1..], line) {
+                Ok(price) => journal.prices.push(price),
+                Err(message) => errors.push(error(EntryProblem::InvalidSyntax(message))),
+            }
+        } else if first == '~' || first == '=' {
+            errors.push(error(EntryProblem::Unsupported(
+                "periodic and automated transactions".to_string(),
+            )));
+        } else if UNSUPPORTED_DIRECTIVES.contains(&keyword) {
+            errors.push(error(EntryProblem::Unsupported(format!(
+                "{keyword} directives"
+            ))));
+        }
+    }
+    if errors.is_empty() {
+        Ok(journal)
+    } else {
+        Err(AccError::InvalidJournal(errors))
+    }
+}
+
+/// Reads `DATE[=DATE2] [*|!] [(CODE)] DESCRIPTION [; COMMENT]`.
+fn read_header(text: &str, line: usize) -> std::result::Result<JournalFileEntry, String> {
+    let (dates, rest) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+    let date_text = dates.split('=').next().unwrap_or_default();
+    let date = read_date(date_text).ok_or_else(|| format!("{date_text} is not a valid date"))?;
+    let (header, comment) = rest.split_once(';').unwrap_or((rest, ""));
+    let mut header = header.trim();
+    let cleared = header.starts_with('*');
+    header = header.trim_start_matches(['*', '!']).trim_start();
+    let mut code = None;
+    if let Some(rest) = header.strip_prefix('(') {
+        if let Some((inner, rest)) = rest.split_once(')') {
+            code = Some(inner.trim().to_string()).filter(|code| !code.is_empty());
+            header = rest.trim_start();
+        }
+    }
+    Ok(JournalFileEntry {
+        line,
+        date,
+        cleared,
+        description: header.trim().to_string(),
+        reference: reference_tag(comment).or(code),
+        postings: vec![],
+    })
+}
+
+fn read_date(text: &str) -> Option<NaiveDate> {
+    ["%Y-%m-%d", "%Y/%m/%d", "%Y.%m.%d"]
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(text, format).ok())
+}
+
+/// Reads `DATE [TIME] COMMODITY PRICE`, the price is an amount with its commodity like `0.95 CHF`.
+fn read_price(text: &str, line: usize) -> std::result::Result<JournalFilePrice, String> {
+    let mut fields = strip_comment(text).split_whitespace();
+    let date_text = fields.next().unwrap_or_default();
+    let date = read_date(date_text).ok_or_else(|| format!("{date_text} is not a valid date"))?;
+    let mut fields = fields.skip_while(|field| field.contains(':')).peekable();
+    let commodity = fields
+        .next()
+        .ok_or_else(|| "price directive without commodity".to_string())?;
+    let price = fields.collect::<Vec<&str>>().join(" ");
+    let (negative, number, currency) = read_number(&price)?;
+    let currency = currency.ok_or_else(|| format!("price {price} has no commodity"))?;
+    if negative {
+        return Err(format!("{price} is not a valid exchange rate"));
+    }
+    Ok(JournalFilePrice {
+        line,
+        date,
+        commodity: commodity.trim_matches('"').to_string(),
+        rate: number.parse()?,
+        currency,
+    })
+}
+
+/// Reads `[*|!] ACCOUNT  [AMOUNT] [@ COST | @@ COST] [= ASSERTION] [; COMMENT]`, the account ends at two spaces or a tab.
+fn read_posting(text: &str) -> std::result::Result<JournalFilePosting, String> {
+    let text = text.trim_start_matches(['*', '!']).trim_start();
+    let (text, exchange_difference) = match text.split_once(';') {
+        Some((posting, comment)) => (posting, has_tag(comment, "exchange-difference")),
+        None => (text, false),
+    };
+    let (account, amount) = match text.find("  ").into_iter().chain(text.find('\t')).min() {
+        Some(end) => (text[..end].trim(), text[end..].trim()),
+        None => (text.trim(), ""),
+    };
+    let (account, virtual_posting) = match account
+        .strip_prefix('(')
+        .and_then(|inner| inner.strip_suffix(')'))
+        .or_else(|| {
+            account
+                .strip_prefix('[')
+                .and_then(|inner| inner.strip_suffix(']'))
+        }) {
+        Some(inner) => (inner, true),
+        None => (account, false),
+    };
+    // A balance assertion only checks the running balance.
+    let amount = amount.split_once('=').map_or(amount, |(amount, _)| amount);
+    let (amount, cost) = match amount.split_once('@') {
+        Some((amount, cost)) => match cost.strip_prefix('@') {
+            Some(total) => {
+                let (total, commodity) = read_amount(total)?;
+                let cost = JournalFileCost::Total {
+                    amount: total.abs(),
+                    commodity,
+                };
+                (amount.trim(), Some(cost))
+            }
+            None => (amount.trim(), Some(JournalFileCost::Unit)),
+        },
+        None => (amount.trim(), None),
+    };
+    let (amount, commodity) = if amount.is_empty() {
+        (None, None)
+    } else {
+        let (amount, commodity) = read_amount(amount)?;
+        (Some(amount), commodity)
+    };
+    Ok(JournalFilePosting {
+        account: account.to_string(),
+        amount,
+        commodity,
+        virtual_posting,
+        cost,
+        exchange_difference,
+    })
+}
+
+/// Reads amounts like `-1,000.50 CHF`, `CHF -1000.50`, `$-20` or `-$20`.
+fn read_amount(text: &str) -> std::result::Result<(AmountEntity, Option<String>), String> {
+    let (negative, number, commodity) = read_number(text)?;
+    let amount: AmountEntity = number
+        .parse()
+        .map_err(|_| format!("{text} is not a valid amount"))?;
+    Ok((if negative { -amount } else { amount }, commodity))
+}
+
+/// Splits a number with an optional commodity into its sign, its digits with `.` as decimal mark and the commodity.
+/// Of `.` and `,` the last one is the decimal mark if both appear, `'` separates thousands.
+fn read_number(text: &str) -> std::result::Result<(bool, String, Option<String>), String> {
+    let invalid = || format!("{text} is not a valid amount");
+    let mut rest = text.trim();
+    let mut negative = false;
+    if let Some(unsigned) = rest.strip_prefix('-') {
+        negative = true;
+        rest = unsigned.trim_start();
+    }
+    let (mut commodity, after) = read_commodity(rest);
+    rest = after.trim_start();
+    if let Some(unsigned) = rest.strip_prefix('-') {
+        negative = !negative;
+        rest = unsigned.trim_start();
+    }
+    let end = rest
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == ',' || c == '\''))
+        .unwrap_or(rest.len());
+    let number = &rest[..end];
+    let (trailing, after) = read_commodity(rest[end..].trim_start());
+    if number.is_empty() || !after.trim().is_empty() || (commodity.is_some() && trailing.is_some())
+    {
+        return Err(invalid());
+    }
+    commodity = commodity.or(trailing);
+    let number = number.replace('\'', "");
+    let number = match (number.rfind('.'), number.rfind(',')) {
+        (Some(point), Some(comma)) if comma > point => number.replace('.', "").replace(',', "."),
+        (_, Some(_)) => number.replace(',', ""),
+        _ => number,
+    };
+    Ok((negative, number, commodity))
+}
+
+/// Splits off a leading commodity, either quoted or up to the first digit, sign or space.
+fn read_commodity(text: &str) -> (Option<String>, &str) {
+    if let Some(quoted) = text.strip_prefix('"') {
+        if let Some((commodity, rest)) = quoted.split_once('"') {
+            return (Some(commodity.to_string()), rest);
+        }
+    }
+    let end = text
+        .find(|c: char| c.is_ascii_digit() || c.is_whitespace() || c == '-' || c == '+')
+        .unwrap_or(text.len());
+    match end {
+        0 => (None, text),
+        _ => (Some(text[..end].to_string()), &text[end..]),
+    }
+}
+
+/// Whether the comment has the tag `name:`, with or without value.
+fn has_tag(comment: &str, name: &str) -> bool {
+    comment.split(',').any(|tag| {
+        tag.trim()
+            .split_once(':')
+            .is_some_and(|(tag, _)| tag.trim() == name)
+    })
+}
+
+fn reference_tag(comment: &str) -> Option<String> {
+    let (_, value) = comment.split_once("reference:")?;
+    let value = value.split(',').next().unwrap_or_default().trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// Directive argument without a trailing comment after `;` or two spaces.
+fn strip_comment(text: &str) -> &str {
+    let text = text.split_once(';').map_or(text, |(value, _)| value);
+    text.split_once("  ")
+        .map_or(text, |(value, _)| value)
+        .trim()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_amount, read_journal_file, JournalFileCost, JournalFilePosting};
+    use crate::model::entity::AmountEntity;
+    use crate::model::entity::ExchangeRate;
+    use crate::model::error::{AccError, EntryProblem};
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_read_journal_file() {
+        let journal = r#"; Buchhaltung 2022
+account Assets:Kasse  ; Bargeld
+account Income:Kursdifferenzen  ; fx-account:
+P 2022-01-01 EUR 0.98 CHF
+P 2022/06/01 12:00:00 EUR CHF 0.9512
+
+2022-01-05 * (R-7) Verkauf  ; reference: REF-1
+    Assets:Kasse                 108.10 CHF
+    ; Barzahlung
+    Income:Ertrag               -100 CHF = -100 CHF
+    Liabilities:Umsatzsteuer
+
+2022/01/06 ! Spesen
+    Expenses:Spesen   EUR 1,234.50
+    Assets:Bank EUR  -1.234,50 EUR @ 0.98 CHF
+    (Budget:Spesen)   -1
+
+2022-07-01 Gutschrift
+    Income:Ertrag                 95.00 CHF
+    Assets:Bank EUR             -100.00 EUR @@ 100.00 CHF
+    Income:Kursdifferenzen         5.00 CHF  ; exchange-difference:
+"#;
+
+        let file = read_journal_file(journal.as_bytes()).unwrap();
+
+        assert_eq!(file.accounts.len(), 2);
+        assert_eq!(file.accounts[0].name, "Assets:Kasse");
+        assert_eq!(file.accounts[0].line, 2);
+        assert!(!file.accounts[0].fx_account);
+        assert!(file.accounts[1].fx_account);
+        assert_eq!(file.prices.len(), 2);
+        assert_eq!(file.prices[0].commodity, "EUR");
+        assert_eq!(file.prices[0].currency, "CHF");
+        assert_eq!(file.prices[0].rate, ExchangeRate::from_micro_units(980_000));
+        assert_eq!(
+            file.prices[1].date,
+            NaiveDate::from_ymd_opt(2022, 6, 1).unwrap()
+        );
+        assert_eq!(file.prices[1].rate, ExchangeRate::from_micro_units(951_200));
+        assert_eq!(file.entries.len(), 3);
+        let sale = &file.entries[0];
+        assert_eq!(sale.line, 7);
+        assert_eq!(sale.date, NaiveDate::from_ymd_opt(2022, 1, 5).unwrap());
+        assert!(sale.cleared);
+        assert_eq!(sale.description, "Verkauf");
+        assert_eq!(sale.reference.as_deref(), Some("REF-1"));
+        assert_eq!(
+            sale.postings[1],
+            JournalFilePosting {
+                account: "Income:Ertrag".to_string(),
+                amount: Some(AmountEntity::new(-100, 0)),
+                commodity: Some("CHF".to_string()),
+                virtual_posting: false,
+                cost: None,
+                exchange_difference: false,
+            }
+        );
+        assert_eq!(sale.postings[2].amount, None);
+        let expenses = &file.entries[1];
+        assert!(!expenses.cleared);
+        assert_eq!(expenses.reference, None);
+        assert_eq!(
+            expenses.postings[0].amount,
+            Some(AmountEntity::new(1234, 50))
+        );
+        assert_eq!(expenses.postings[0].commodity.as_deref(), Some("EUR"));
+        assert_eq!(expenses.postings[1].account, "Assets:Bank EUR");
+        assert_eq!(
+            expenses.postings[1].amount,
+            Some(AmountEntity::new(-1234, 50))
+        );
+        assert_eq!(expenses.postings[1].cost, Some(JournalFileCost::Unit));
+        assert!(expenses.postings[2].virtual_posting);
+        assert_eq!(expenses.postings[2].commodity, None);
+        let transfer = &file.entries[2];
+        assert_eq!(
+            transfer.postings[1].cost,
+            Some(JournalFileCost::Total {
+                amount: AmountEntity::new(100, 0),
+                commodity: Some("CHF".to_string()),
+            })
+        );
+        assert!(!transfer.postings[1].exchange_difference);
+        assert!(transfer.postings[2].exchange_difference);
+    }
+
+    #[test]
+    fn test_read_journal_file_reports_every_problem() {
+        let journal = "include other.journal\n\n2022-13-01 Falsch\n    Assets:Kasse  1.2.3\n\n2022-01-02 Richtig\n    Assets:Kasse  12 CHF CHF\n";
+
+        match read_journal_file(journal.as_bytes()) {
+            Err(AccError::InvalidJournal(errors)) => {
+                let lines: Vec<usize> = errors.iter().map(|error| error.line).collect();
+                assert_eq!(lines, vec![1, 3, 7]);
+                assert_eq!(
+                    errors[0].problem,
+                    EntryProblem::Unsupported("include directives".to_string())
+                );
+            }
+            other => panic!("unexpected result {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_read_amount() {
+        let amount =
+            |text| read_amount(text).map(|(amount, commodity)| (amount.to_string(), commodity));
+        assert_eq!(
+            amount("$-20"),
+            Ok(("-20.00".to_string(), Some("$".to_string())))
+        );
+        assert_eq!(
+            amount("-$20"),
+            Ok(("-20.00".to_string(), Some("$".to_string())))
+        );
+        assert_eq!(amount("1'000.5"), Ok(("1000.50".to_string(), None)));
+        assert_eq!(
+            amount("\"ACME 1\" 3"),
+            Ok(("3.00".to_string(), Some("ACME 1".to_string())))
+        );
+        assert!(amount("0.001 CHF").is_err());
+    }
+}
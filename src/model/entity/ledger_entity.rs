@@ -1,12 +1,253 @@
 use super::amount_entity::AmountEntity;
+use super::exchange_rate::ExchangeRate;
+use super::percentage::Percentage;
+use super::transaction_entity::TaxCode;
+use crate::model::error::AccError;
+use chrono::{Months, NaiveDate};
 #[cfg(test)]
 use mockall::automock;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 pub struct LedgerEntity {
     balance: Balance,
     income: Income,
+    #[serde(default, skip_serializing_if = "CurrencyEntity::is_default")]
+    currency: CurrencyEntity,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    vat: Option<VatEntity>,
+    #[serde(
+        default,
+        rename = "fiscal-year",
+        skip_serializing_if = "Option::is_none"
+    )]
+    fiscal_year: Option<FiscalYearEntity>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    rules: Vec<RuleEntity>,
+}
+
+/// Top-level accounts of every group, to build a definition from scratch.
+#[derive(PartialEq, Debug, Clone, Default)]
+pub struct AccountGroups {
+    pub working_capital: Vec<AccountEntity>,
+    pub fixed_assets: Vec<AccountEntity>,
+    pub equity: Vec<AccountEntity>,
+    pub debt_capital: Vec<AccountEntity>,
+    pub revenue: Vec<AccountEntity>,
+    pub expense: Vec<AccountEntity>,
+}
+
+impl LedgerEntity {
+    /// Definition with the accounts of `groups` and neither VAT, fiscal year nor rules.
+    pub fn from_groups(groups: AccountGroups, currency: CurrencyEntity) -> Self {
+        LedgerEntity {
+            balance: Balance {
+                active: ActiveBalance {
+                    working_capital: groups.working_capital,
+                    fixed_assets: groups.fixed_assets,
+                },
+                passive: PassiveBalance {
+                    equity: groups.equity,
+                    debt_capital: groups.debt_capital,
+                },
+            },
+            income: Income {
+                revenue: groups.revenue,
+                expense: groups.expense,
+            },
+            currency,
+            vat: None,
+            fiscal_year: None,
+            rules: vec![],
+        }
+    }
+
+    pub fn currency(&self) -> &CurrencyEntity {
+        &self.currency
+    }
+
+    pub fn vat(&self) -> Option<&VatEntity> {
+        self.vat.as_ref()
+    }
+
+    pub fn fiscal_year(&self) -> Option<&FiscalYearEntity> {
+        self.fiscal_year.as_ref()
+    }
+
+    pub fn rules(&self) -> &[RuleEntity] {
+        &self.rules
+    }
+
+    /// Copy of the definition with the start amounts replaced by `starts`, keyed by full account name.
+    /// Accounts missing in `starts` keep their start amount.
+    pub fn with_starts(&self, starts: &HashMap<String, AccountStart>) -> LedgerEntity {
+        let mut definition = self.clone();
+        for group in [
+            &mut definition.balance.active.working_capital,
+            &mut definition.balance.active.fixed_assets,
+            &mut definition.balance.passive.equity,
+            &mut definition.balance.passive.debt_capital,
+            &mut definition.income.revenue,
+            &mut definition.income.expense,
+        ] {
+            for account in group.iter_mut() {
+                account.replace_starts(None, starts);
+            }
+        }
+        definition
+    }
+
+    /// Copy of the definition for the following fiscal year, see [LedgerEntity::with_starts].
+    pub fn carry_forward(
+        &self,
+        starts: &HashMap<String, AccountStart>,
+    ) -> Result<LedgerEntity, AccError> {
+        let mut definition = self.with_starts(starts);
+        definition.fiscal_year = self
+            .fiscal_year
+            .as_ref()
+            .map(FiscalYearEntity::next)
+            .transpose()?;
+        Ok(definition)
+    }
+}
+
+/// Opening balance of an account in its own currency, foreign currency accounts also have its value
+/// in the base currency.
+#[derive(PartialEq, Debug, Clone, Copy, Default)]
+pub struct AccountStart {
+    pub amount: AmountEntity,
+    pub value: Option<AmountEntity>,
+}
+
+/// Accounts the tax split off taxed transactions is booked onto.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct VatEntity {
+    /// Input tax (Vorsteuer) on purchases.
+    #[serde(rename = "input-tax")]
+    pub input_tax: String,
+    /// Output tax (Umsatzsteuer) on sales.
+    #[serde(rename = "output-tax")]
+    pub output_tax: String,
+    /// Rates in addition to the Swiss rates, e.g. for a future change of the rates.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rates: Vec<VatRateEntity>,
+}
+
+/// VAT rate of `code` in percent, valid from `date` until the next rate of the same code.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct VatRateEntity {
+    pub code: TaxCode,
+    pub date: NaiveDate,
+    pub rate: Percentage,
+}
+
+/// Base currency of the ledger and the exchange rates of all foreign currencies.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct CurrencyEntity {
+    #[serde(default = "default_base_currency")]
+    pub base: String,
+    /// Account realized exchange rate gains and losses are booked onto.
+    #[serde(
+        default,
+        rename = "fx-account",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub fx_account: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rates: Vec<RateEntity>,
+}
+
+fn default_base_currency() -> String {
+    String::from("CHF")
+}
+
+impl CurrencyEntity {
+    fn is_default(&self) -> bool {
+        self == &CurrencyEntity::default()
+    }
+}
+
+impl Default for CurrencyEntity {
+    fn default() -> Self {
+        CurrencyEntity {
+            base: default_base_currency(),
+            fx_account: None,
+            rates: vec![],
+        }
+    }
+}
+
+/// Fiscal year of the ledger, either one period or divided into monthly periods.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct FiscalYearEntity {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub monthly: bool,
+    /// Numbers of the periods no transactions may be added to anymore, the first period is 1.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub locked: Vec<u32>,
+}
+
+impl FiscalYearEntity {
+    /// The twelve months after the end of the year with the same periods, none of them locked.
+    fn next(&self) -> Result<Self, AccError> {
+        let start = self.end.succ_opt();
+        let end = start
+            .and_then(|start| start.checked_add_months(Months::new(12)))
+            .and_then(|end| end.pred_opt());
+        match (start, end) {
+            (Some(start), Some(end)) => Ok(FiscalYearEntity {
+                start,
+                end,
+                monthly: self.monthly,
+                locked: vec![],
+            }),
+            _ => Err(AccError::InvalidFiscalYear(format!(
+                "no fiscal year follows the one ending {}",
+                self.end
+            ))),
+        }
+    }
+}
+
+/// Assigns the counter-account of imported bank entries. Every given criterion has to match,
+/// the first matching rule of the ledger wins.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct RuleEntity {
+    /// Regular expression the description has to match.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Smallest amount without sign, inclusive.
+    #[serde(
+        default,
+        rename = "min-amount",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub min_amount: Option<AmountEntity>,
+    /// Largest amount without sign, inclusive.
+    #[serde(
+        default,
+        rename = "max-amount",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub max_amount: Option<AmountEntity>,
+    /// Name of the other party of the payment, ignoring case.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub counterparty: Option<String>,
+    pub account: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tax: Option<TaxCode>,
+}
+
+/// Value of one unit of `currency` in the base currency, valid from `date` until the next rate.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct RateEntity {
+    pub currency: String,
+    pub date: NaiveDate,
+    pub rate: ExchangeRate,
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
@@ -39,17 +280,70 @@ struct PassiveBalance {
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 pub struct AccountEntity {
     pub name: String,
+    /// Account number of the chart of accounts, e.g. 1000 for Kasse.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub number: Option<u32>,
+    #[serde(default)]
     pub start: AmountEntity,
+    /// Opening balance of a foreign currency account in the base currency, as written when closing a year.
+    /// Without it the start is converted with the earliest rate of the currency.
+    #[serde(
+        default,
+        rename = "start-value",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub start_value: Option<AmountEntity>,
+    /// Currency the account is kept in, the base currency if not set. Sub-accounts inherit it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+    /// Sub-accounts, their full name is prefixed with the name of this account, e.g. "Bank:UBS".
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub accounts: Vec<AccountEntity>,
+}
+
+impl AccountEntity {
+    fn replace_starts(&mut self, parent: Option<&str>, starts: &HashMap<String, AccountStart>) {
+        let name = match parent {
+            Some(parent) => format!("{parent}:{}", self.name),
+            None => self.name.clone(),
+        };
+        if let Some(start) = starts.get(&name) {
+            self.start = start.amount;
+            self.start_value = start.value;
+        }
+        for account in self.accounts.iter_mut() {
+            account.replace_starts(Some(&name), starts);
+        }
+    }
 }
 
 #[cfg(test)]
 impl AccountEntity {
-    pub fn new(name: &str, start: u128) -> Self {
+    pub fn new(name: &str, start: i64) -> Self {
         AccountEntity {
             name: String::from(name),
+            number: None,
             start: AmountEntity::new(start, 0),
+            start_value: None,
+            currency: None,
+            accounts: vec![],
         }
     }
+
+    pub fn with_accounts(mut self, accounts: Vec<AccountEntity>) -> Self {
+        self.accounts = accounts;
+        self
+    }
+
+    pub fn with_number(mut self, number: u32) -> Self {
+        self.number = Some(number);
+        self
+    }
+
+    pub fn with_currency(mut self, currency: &str) -> Self {
+        self.currency = Some(currency.to_string());
+        self
+    }
 }
 pub trait AccountsReader {
     fn get_equities(&self) -> Vec<AccountEntity>;
@@ -63,50 +357,37 @@ pub trait AccountsReader {
 #[cfg_attr(test, automock)]
 impl AccountsReader for LedgerEntity {
     fn get_equities(&self) -> Vec<AccountEntity> {
-        self.balance
-            .passive
-            .equity
-            .iter()
-            .map(|e| e.clone())
-            .collect()
+        self.balance.passive.equity.to_vec()
     }
     fn get_debt_capital(&self) -> Vec<AccountEntity> {
-        self.balance
-            .passive
-            .debt_capital
-            .iter()
-            .map(|e| e.clone())
-            .collect()
+        self.balance.passive.debt_capital.to_vec()
     }
     fn get_fixed_assets(&self) -> Vec<AccountEntity> {
-        self.balance
-            .active
-            .fixed_assets
-            .iter()
-            .map(|e| e.clone())
-            .collect()
+        self.balance.active.fixed_assets.to_vec()
     }
     fn get_working_capital(&self) -> Vec<AccountEntity> {
-        self.balance
-            .active
-            .working_capital
-            .iter()
-            .map(|e| e.clone())
-            .collect()
+        self.balance.active.working_capital.to_vec()
     }
     fn get_revenue(&self) -> Vec<AccountEntity> {
-        self.income.revenue.iter().map(|e| e.clone()).collect()
+        self.income.revenue.to_vec()
     }
     fn get_expenses(&self) -> Vec<AccountEntity> {
-        self.income.expense.iter().map(|e| e.clone()).collect()
+        self.income.expense.to_vec()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::super::amount_entity::AmountEntity;
-    use super::{AccountEntity, AccountsReader, LedgerEntity};
+    use super::super::exchange_rate::ExchangeRate;
+    use super::{
+        AccError, AccountEntity, AccountStart, AccountsReader, CurrencyEntity, FiscalYearEntity,
+        LedgerEntity, RateEntity, RuleEntity, VatEntity, VatRateEntity,
+    };
     use super::{ActiveBalance, Balance, Income, PassiveBalance};
+    use crate::model::entity::{Percentage, TaxCode};
+    use chrono::NaiveDate;
+    use std::collections::HashMap;
 
     #[test]
     fn test_with_getter() -> Result<(), serde_yaml::Error> {
@@ -173,6 +454,10 @@ income:
                 revenue: vec![],
                 expense: vec![],
             },
+            currency: CurrencyEntity::default(),
+            vat: None,
+            fiscal_year: None,
+            rules: vec![],
         };
 
         let serialized = serde_yaml::to_string(&definition)?;
@@ -180,14 +465,287 @@ income:
         Ok(())
     }
 
-    fn verify_account(accounts: Vec<AccountEntity>, name: &str, start: u128) -> () {
+    #[test]
+    fn test_account_with_number() -> Result<(), serde_yaml::Error> {
+        let account: AccountEntity = serde_yaml::from_str("name: Kasse\nnumber: 1000\nstart: 10")?;
+        assert_eq!(account, AccountEntity::new("Kasse", 10).with_number(1000));
+        assert_eq!(
+            serde_yaml::to_string(&account)?,
+            "name: Kasse\nnumber: 1000\nstart: 10.0\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_account_with_sub_accounts() -> Result<(), serde_yaml::Error> {
+        let account: AccountEntity = serde_yaml::from_str(
+            r#"
+name: Bank
+accounts:
+  - name: UBS
+    start: 10
+  - name: PostFinance
+    start: 20"#,
+        )?;
+        assert_eq!(
+            account,
+            AccountEntity::new("Bank", 0).with_accounts(vec![
+                AccountEntity::new("UBS", 10),
+                AccountEntity::new("PostFinance", 20)
+            ])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_currency_definition() -> Result<(), serde_yaml::Error> {
+        let definition = r#"
+balance:
+  active:
+    working-capital:
+      - name: Bank EUR
+        currency: EUR
+        start: 1000
+    fixed-assets: []
+  passive:
+    equity: []
+    debt-capital: []
+income:
+  revenue: []
+  expense: []
+currency:
+  base: CHF
+  fx-account: Kursdifferenzen
+  rates:
+    - currency: EUR
+      date: 2022-01-01
+      rate: 0.98"#;
+
+        let ledger_definition: LedgerEntity = serde_yaml::from_str(definition)?;
+
+        assert_eq!(
+            ledger_definition.get_working_capital(),
+            vec![AccountEntity::new("Bank EUR", 1000).with_currency("EUR")]
+        );
+        assert_eq!(
+            ledger_definition.currency(),
+            &CurrencyEntity {
+                base: "CHF".to_string(),
+                fx_account: Some("Kursdifferenzen".to_string()),
+                rates: vec![RateEntity {
+                    currency: "EUR".to_string(),
+                    date: chrono::NaiveDate::from_ymd_opt(2022, 1, 1).unwrap(),
+                    rate: ExchangeRate::from_micro_units(980_000),
+                }],
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_vat_definition() -> Result<(), serde_yaml::Error> {
+        let definition = r#"
+balance:
+  active:
+    working-capital: []
+    fixed-assets: []
+  passive:
+    equity: []
+    debt-capital: []
+income:
+  revenue: []
+  expense: []
+vat:
+  input-tax: Vorsteuer
+  output-tax: Umsatzsteuer
+  rates:
+    - code: standard
+      date: 2030-01-01
+      rate: 8.5"#;
+
+        let ledger_definition: LedgerEntity = serde_yaml::from_str(definition)?;
+
+        assert_eq!(
+            ledger_definition.vat(),
+            Some(&VatEntity {
+                input_tax: "Vorsteuer".to_string(),
+                output_tax: "Umsatzsteuer".to_string(),
+                rates: vec![VatRateEntity {
+                    code: TaxCode::Standard,
+                    date: NaiveDate::from_ymd_opt(2030, 1, 1).unwrap(),
+                    rate: Percentage::from_basis_points(850),
+                }],
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_rules_definition() -> Result<(), serde_yaml::Error> {
+        let definition = r#"
+balance:
+  active:
+    working-capital: []
+    fixed-assets: []
+  passive:
+    equity: []
+    debt-capital: []
+income:
+  revenue: []
+  expense: []
+rules:
+  - description: "(?i)migros|coop"
+    max-amount: 500
+    account: Aufwand
+    tax: reduced
+  - counterparty: Muster AG
+    account: Ertrag"#;
+
+        let ledger_definition: LedgerEntity = serde_yaml::from_str(definition)?;
+
+        assert_eq!(
+            ledger_definition.rules(),
+            &[
+                RuleEntity {
+                    description: Some("(?i)migros|coop".to_string()),
+                    min_amount: None,
+                    max_amount: Some(AmountEntity::new(500, 0)),
+                    counterparty: None,
+                    account: "Aufwand".to_string(),
+                    tax: Some(TaxCode::Reduced),
+                },
+                RuleEntity {
+                    description: None,
+                    min_amount: None,
+                    max_amount: None,
+                    counterparty: Some("Muster AG".to_string()),
+                    account: "Ertrag".to_string(),
+                    tax: None,
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_with_starts() -> Result<(), serde_yaml::Error> {
+        let definition = r#"
+balance:
+  active:
+    working-capital:
+      - name: Bank
+        accounts:
+          - name: UBS
+            start: 10
+    fixed-assets: []
+  passive:
+    equity:
+      - name: Eigenkapital
+        start: 10
+    debt-capital: []
+income:
+  revenue:
+    - name: Ertrag
+      start: 5
+  expense: []"#;
+        let ledger_definition: LedgerEntity = serde_yaml::from_str(definition)?;
+        let starts = HashMap::from([
+            (
+                "Bank:UBS".to_string(),
+                AccountStart {
+                    amount: AmountEntity::new(30, 0),
+                    value: None,
+                },
+            ),
+            ("Ertrag".to_string(), AccountStart::default()),
+        ]);
+
+        let carried_forward = ledger_definition.with_starts(&starts);
+
+        assert_eq!(
+            carried_forward.get_working_capital()[0].accounts,
+            vec![AccountEntity::new("UBS", 30)]
+        );
+        verify_account(carried_forward.get_revenue(), "Ertrag", 0);
+        verify_account(carried_forward.get_equities(), "Eigenkapital", 10);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fiscal_year_carried_forward() -> Result<(), serde_yaml::Error> {
+        let definition = r#"
+balance:
+  active:
+    working-capital: []
+    fixed-assets: []
+  passive:
+    equity: []
+    debt-capital: []
+income:
+  revenue: []
+  expense: []
+fiscal-year:
+  start: 2022-07-01
+  end: 2023-06-30
+  monthly: true
+  locked: [1, 2]"#;
+        let ledger_definition: LedgerEntity = serde_yaml::from_str(definition)?;
+
+        let next_year = ledger_definition
+            .carry_forward(&HashMap::new())
+            .expect("next fiscal year");
+
+        let date =
+            |year: i32, month: u32, day: u32| NaiveDate::from_ymd_opt(year, month, day).unwrap();
+        assert_eq!(
+            next_year.fiscal_year(),
+            Some(&FiscalYearEntity {
+                start: date(2023, 7, 1),
+                end: date(2024, 6, 30),
+                monthly: true,
+                locked: vec![],
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_fiscal_year_ending_in_february_carried_forward() {
+        let date =
+            |year: i32, month: u32, day: u32| NaiveDate::from_ymd_opt(year, month, day).unwrap();
+        let fiscal_year = FiscalYearEntity {
+            start: date(2022, 3, 1),
+            end: date(2023, 2, 28),
+            monthly: false,
+            locked: vec![],
+        };
+
+        let next_year = fiscal_year.next().expect("next fiscal year");
+
+        assert_eq!(next_year.start, date(2023, 3, 1));
+        assert_eq!(next_year.end, date(2024, 2, 29));
+        let last_year = FiscalYearEntity {
+            end: NaiveDate::MAX,
+            ..fiscal_year
+        };
+        assert!(matches!(
+            last_year.next(),
+            Err(AccError::InvalidFiscalYear(_))
+        ));
+    }
+
+    fn verify_account(accounts: Vec<AccountEntity>, name: &str, start: i64) {
         assert_eq!(accounts.len(), 1);
         let account = accounts.first().expect("first account not found");
         assert_eq!(
             account,
             &AccountEntity {
                 name: name.to_string(),
+                number: None,
                 start: AmountEntity::new(start, 0),
+                start_value: None,
+                currency: None,
+                accounts: vec![],
             }
         )
     }
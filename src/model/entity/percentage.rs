@@ -0,0 +1,80 @@
+use super::fixed_point::{self, DecimalVisitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Number of decimal places of a percentage, so it is stored in basis points.
+const SCALE: u32 = 2;
+
+/// Non-negative percentage like a VAT rate, stored as an exact number of basis points.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy, Default)]
+pub struct Percentage {
+    basis_points: i64,
+}
+
+impl Percentage {
+    pub fn from_basis_points(basis_points: i64) -> Self {
+        Percentage { basis_points }
+    }
+
+    /// Hundredths of a percent, e.g. 810 for 8.1%.
+    pub fn basis_points(&self) -> i64 {
+        self.basis_points
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.basis_points == 0
+    }
+}
+
+/// Written without percent sign and with at least one decimal place, e.g. `8.1`.
+impl Display for Percentage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.pad(&fixed_point::format(self.basis_points, SCALE, 1))
+    }
+}
+
+impl FromStr for Percentage {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let basis_points = fixed_point::parse(s, SCALE)?;
+        if basis_points < 0 {
+            return Err(format!("{s} is not a valid percentage"));
+        }
+        Ok(Percentage::from_basis_points(basis_points))
+    }
+}
+
+impl Serialize for Percentage {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        fixed_point::serialize(self.basis_points, &self.to_string(), serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Percentage {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(DecimalVisitor::new("a percentage like 8.1"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    #[test]
+    fn test_percentage() -> Result<(), serde_yaml::Error> {
+        let rate: super::Percentage = serde_yaml::from_str("7.7")?;
+        assert_eq!(rate.basis_points(), 770);
+        assert_eq!(rate.to_string(), "7.7");
+        assert_eq!(serde_yaml::to_string(&rate)?, "7.7\n");
+        assert!(serde_yaml::from_str::<super::Percentage>("-2.5").is_err());
+        assert!(serde_yaml::from_str::<super::Percentage>("3.755").is_err());
+        Ok(())
+    }
+}
@@ -3,33 +3,291 @@ use chrono::NaiveDate;
 #[cfg(test)]
 use mockall::automock;
 use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
 
 pub trait TransactionAccountReader {
-    fn read_debitor_account(&self) -> &str;
-    fn read_creditor_account(&self) -> &str;
     fn read_description(&self) -> &str;
+    fn read_date(&self) -> NaiveDate;
+    /// All postings of the transaction, a single line transaction has one debit and one credit line.
+    fn read_lines(&self) -> Vec<PostingLine>;
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum PostingSide {
+    Debit,
+    Credit,
+}
+
+impl PostingSide {
+    pub fn opposite(&self) -> Self {
+        match self {
+            PostingSide::Debit => PostingSide::Credit,
+            PostingSide::Credit => PostingSide::Debit,
+        }
+    }
+}
+
+/// Swiss VAT (MWST) code of a taxed amount.
+#[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy)]
+#[serde(rename_all = "kebab-case")]
+pub enum TaxCode {
+    Standard,
+    Reduced,
+    Accommodation,
+    Exempt,
+}
+
+impl Display for TaxCode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let label = match self {
+            TaxCode::Standard => "standard",
+            TaxCode::Reduced => "reduced",
+            TaxCode::Accommodation => "accommodation",
+            TaxCode::Exempt => "exempt",
+        };
+        f.pad(label)
+    }
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct PostingLine {
+    pub account: String,
+    pub side: PostingSide,
+    pub amount: AmountEntity,
+    /// Currency of the amount, the base currency if not set.
+    pub currency: Option<String>,
+    /// Tax code if the amount includes VAT.
+    pub tax: Option<TaxCode>,
+}
+
+impl PostingLine {
+    pub fn debit(account: &str, amount: AmountEntity) -> Self {
+        PostingLine {
+            account: account.to_string(),
+            side: PostingSide::Debit,
+            amount,
+            currency: None,
+            tax: None,
+        }
+    }
+
+    pub fn credit(account: &str, amount: AmountEntity) -> Self {
+        PostingLine {
+            account: account.to_string(),
+            side: PostingSide::Credit,
+            amount,
+            currency: None,
+            tax: None,
+        }
+    }
+
+    pub fn with_currency(mut self, currency: &str) -> Self {
+        self.currency = Some(currency.to_string());
+        self
+    }
+
+    pub fn with_tax(mut self, tax: TaxCode) -> Self {
+        self.tax = Some(tax);
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Postings {
+    Single {
+        amount: AmountEntity,
+        currency: Option<String>,
+        tax: Option<TaxCode>,
+        deb: String,
+        cred: String,
+    },
+    Compound(Vec<PostingLine>),
+}
+
+/// A transaction either books one amount from `cred` to `deb` or lists its posting `lines`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(try_from = "TransactionFields", into = "TransactionFields")]
 pub struct TransactionEntity {
     description: String,
-    amount: AmountEntity,
-    deb: String,
-    cred: String,
+    postings: Postings,
     date: NaiveDate,
+    /// Reference of the bank the transaction was imported from.
+    reference: Option<String>,
+    /// Whether the transaction was matched with a bank statement.
+    reconciled: bool,
 }
 
-#[cfg_attr(test, automock)]
-impl TransactionAccountReader for TransactionEntity {
-    fn read_creditor_account(&self) -> &str {
-        &self.cred
+/// Yaml representation of [TransactionEntity].
+#[derive(Serialize, Deserialize)]
+struct TransactionFields {
+    description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    amount: Option<AmountEntity>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    currency: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tax: Option<TaxCode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    deb: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cred: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    lines: Option<Vec<PostingLineFields>>,
+    date: NaiveDate,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reference: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    reconciled: bool,
+}
+
+/// Yaml representation of a [PostingLine], exactly one of `deb` and `cred` is set.
+#[derive(Serialize, Deserialize)]
+struct PostingLineFields {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    deb: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cred: Option<String>,
+    amount: AmountEntity,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    currency: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tax: Option<TaxCode>,
+}
+
+impl TryFrom<TransactionFields> for TransactionEntity {
+    type Error = String;
+
+    fn try_from(fields: TransactionFields) -> Result<Self, Self::Error> {
+        let postings = match (fields.amount, fields.deb, fields.cred, fields.lines) {
+            (Some(amount), Some(deb), Some(cred), None) => Postings::Single {
+                amount,
+                currency: fields.currency,
+                tax: fields.tax,
+                deb,
+                cred,
+            },
+            (None, None, None, Some(lines))
+                if fields.currency.is_none() && fields.tax.is_none() =>
+            {
+                Postings::Compound(
+                    lines
+                        .into_iter()
+                        .map(|line| {
+                            let posting = match (line.deb, line.cred) {
+                                (Some(deb), None) => PostingLine::debit(&deb, line.amount),
+                                (None, Some(cred)) => PostingLine::credit(&cred, line.amount),
+                                _ => {
+                                    return Err(format!(
+                                        "posting line of transaction {} needs either deb or cred",
+                                        fields.description
+                                    ))
+                                }
+                            };
+                            Ok(PostingLine {
+                                currency: line.currency,
+                                tax: line.tax,
+                                ..posting
+                            })
+                        })
+                        .collect::<Result<Vec<PostingLine>, String>>()?,
+                )
+            }
+            _ => {
+                return Err(format!(
+                    "transaction {} needs either amount, deb and cred or lines",
+                    fields.description
+                ))
+            }
+        };
+        Ok(TransactionEntity {
+            description: fields.description,
+            postings,
+            date: fields.date,
+            reference: fields.reference,
+            reconciled: fields.reconciled,
+        })
     }
-    fn read_debitor_account(&self) -> &str {
-        &self.deb
+}
+
+impl From<TransactionEntity> for TransactionFields {
+    fn from(transaction: TransactionEntity) -> Self {
+        let fields = TransactionFields {
+            description: transaction.description,
+            amount: None,
+            currency: None,
+            tax: None,
+            deb: None,
+            cred: None,
+            lines: None,
+            date: transaction.date,
+            reference: transaction.reference,
+            reconciled: transaction.reconciled,
+        };
+        match transaction.postings {
+            Postings::Single {
+                amount,
+                currency,
+                tax,
+                deb,
+                cred,
+            } => TransactionFields {
+                amount: Some(amount),
+                currency,
+                tax,
+                deb: Some(deb),
+                cred: Some(cred),
+                ..fields
+            },
+            Postings::Compound(lines) => TransactionFields {
+                lines: Some(
+                    lines
+                        .into_iter()
+                        .map(|line| PostingLineFields {
+                            deb: (line.side == PostingSide::Debit).then(|| line.account.clone()),
+                            cred: (line.side == PostingSide::Credit).then_some(line.account),
+                            amount: line.amount,
+                            currency: line.currency,
+                            tax: line.tax,
+                        })
+                        .collect(),
+                ),
+                ..fields
+            },
+        }
     }
+}
+
+#[cfg_attr(test, automock)]
+impl TransactionAccountReader for TransactionEntity {
     fn read_description(&self) -> &str {
         &self.description
     }
+    fn read_date(&self) -> NaiveDate {
+        self.date
+    }
+    fn read_lines(&self) -> Vec<PostingLine> {
+        match &self.postings {
+            Postings::Single {
+                amount,
+                currency,
+                tax,
+                deb,
+                cred,
+            } => vec![
+                PostingLine {
+                    currency: currency.clone(),
+                    tax: *tax,
+                    ..PostingLine::debit(deb, *amount)
+                },
+                PostingLine {
+                    currency: currency.clone(),
+                    tax: *tax,
+                    ..PostingLine::credit(cred, *amount)
+                },
+            ],
+            Postings::Compound(lines) => lines.clone(),
+        }
+    }
 }
 
 impl TransactionEntity {
@@ -42,17 +300,81 @@ impl TransactionEntity {
     ) -> Self {
         TransactionEntity {
             description,
-            amount,
-            deb,
-            cred,
+            postings: Postings::Single {
+                amount,
+                currency: None,
+                tax: None,
+                deb,
+                cred,
+            },
             date,
+            reference: None,
+            reconciled: false,
         }
     }
+
+    /// Sets the tax code of all amounts of the transaction.
+    pub fn with_tax(mut self, tax: TaxCode) -> Self {
+        match &mut self.postings {
+            Postings::Single { tax: single, .. } => *single = Some(tax),
+            Postings::Compound(lines) => {
+                for line in lines.iter_mut() {
+                    line.tax = Some(tax);
+                }
+            }
+        }
+        self
+    }
+
+    /// Sets the currency of all amounts of the transaction.
+    pub fn with_currency(mut self, currency: String) -> Self {
+        match &mut self.postings {
+            Postings::Single {
+                currency: single, ..
+            } => *single = Some(currency),
+            Postings::Compound(lines) => {
+                for line in lines.iter_mut() {
+                    line.currency = Some(currency.clone());
+                }
+            }
+        }
+        self
+    }
+
+    pub fn compound(description: String, lines: Vec<PostingLine>, date: NaiveDate) -> Self {
+        TransactionEntity {
+            description,
+            postings: Postings::Compound(lines),
+            date,
+            reference: None,
+            reconciled: false,
+        }
+    }
+
+    pub fn with_reference(mut self, reference: String) -> Self {
+        self.reference = Some(reference);
+        self
+    }
+
+    /// Reference of the bank the transaction was imported from.
+    pub fn reference(&self) -> Option<&str> {
+        self.reference.as_deref()
+    }
+
+    pub fn is_reconciled(&self) -> bool {
+        self.reconciled
+    }
+
+    pub fn mark_reconciled(&mut self) {
+        self.reconciled = true;
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::model::entity::transaction_entity::TransactionEntity;
+    use crate::model::entity::transaction_entity::{
+        PostingLine, TaxCode, TransactionAccountReader, TransactionEntity,
+    };
     use crate::model::entity::AmountEntity;
     use chrono::NaiveDate;
     use serde::{Deserialize, Serialize};
@@ -75,9 +397,112 @@ transactions:
         let ledger_transactions: TransactionsWrapper = serde_yaml::from_str(definition)?;
 
         assert_eq!(ledger_transactions.transactions.len(), 1);
+        assert_eq!(
+            ledger_transactions.transactions[0].read_lines(),
+            vec![
+                PostingLine::debit("Maschinen", AmountEntity::new(100, 0)),
+                PostingLine::credit("Aufwand", AmountEntity::new(100, 0)),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialization_compound() -> Result<(), serde_yaml::Error> {
+        let definition = r#"
+transactions:
+  - date: 2022-03-01
+    description: Lieferantenrechnung
+    lines:
+      - deb: Material
+        amount: 100.00
+      - deb: Vorsteuer
+        amount: 8.10
+      - cred: Kreditoren
+        amount: 108.10"#;
+
+        let ledger_transactions: TransactionsWrapper = serde_yaml::from_str(definition)?;
+
+        assert_eq!(
+            ledger_transactions.transactions[0].read_lines(),
+            vec![
+                PostingLine::debit("Material", AmountEntity::new(100, 0)),
+                PostingLine::debit("Vorsteuer", AmountEntity::new(8, 10)),
+                PostingLine::credit("Kreditoren", AmountEntity::new(108, 10)),
+            ]
+        );
+        let serialized = serde_yaml::to_string(&ledger_transactions)?;
+        let reparsed: TransactionsWrapper = serde_yaml::from_str(&serialized)?;
+        assert_eq!(
+            reparsed.transactions[0].read_lines(),
+            ledger_transactions.transactions[0].read_lines()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialization_with_currency() -> Result<(), serde_yaml::Error> {
+        let definition = r#"
+transactions:
+  - date: 2022-04-01
+    description: Wechsel
+    deb: Bank EUR
+    cred: Bank
+    amount: 500.00
+    currency: EUR"#;
+
+        let ledger_transactions: TransactionsWrapper = serde_yaml::from_str(definition)?;
+
+        assert_eq!(
+            ledger_transactions.transactions[0].read_lines(),
+            vec![
+                PostingLine::debit("Bank EUR", AmountEntity::new(500, 0)).with_currency("EUR"),
+                PostingLine::credit("Bank", AmountEntity::new(500, 0)).with_currency("EUR"),
+            ]
+        );
         Ok(())
     }
 
+    #[test]
+    fn test_deserialization_with_tax() -> Result<(), serde_yaml::Error> {
+        let definition = r#"
+transactions:
+  - date: 2022-04-01
+    description: Verkauf
+    deb: Kasse
+    cred: Ertrag
+    amount: 108.10
+    tax: standard"#;
+
+        let ledger_transactions: TransactionsWrapper = serde_yaml::from_str(definition)?;
+
+        assert_eq!(
+            ledger_transactions.transactions[0].read_lines()[1],
+            PostingLine::credit("Ertrag", AmountEntity::new(108, 10)).with_tax(TaxCode::Standard)
+        );
+        let serialized = serde_yaml::to_string(&ledger_transactions)?;
+        assert!(serialized.contains("tax: standard"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialization_mixed_forms() {
+        let definition = r#"
+transactions:
+  - date: 2022-03-01
+    description: Gemischt
+    deb: Material
+    amount: 100.00
+    lines:
+      - cred: Kreditoren
+        amount: 100.00"#;
+
+        let ledger_transactions: Result<TransactionsWrapper, serde_yaml::Error> =
+            serde_yaml::from_str(definition);
+
+        assert!(ledger_transactions.is_err());
+    }
+
     #[test]
     fn test_serialization() -> Result<(), serde_yaml::Error> {
         let expected_str = r#"transactions:
@@ -103,4 +528,36 @@ transactions:
         assert_eq!(serialized, expected_str);
         Ok(())
     }
+
+    #[test]
+    fn test_serialization_of_imported_transaction() -> Result<(), serde_yaml::Error> {
+        let expected_str = r#"transactions:
+- description: Rechnung 42
+  amount: 1500.0
+  deb: Bank
+  cred: Ertrag
+  date: 2022-03-01
+  reference: REF-1
+  reconciled: true
+"#;
+        let mut transaction = TransactionEntity::new(
+            String::from("Rechnung 42"),
+            AmountEntity::new(1500, 0),
+            String::from("Bank"),
+            String::from("Ertrag"),
+            NaiveDate::from_ymd_opt(2022, 3, 1).unwrap(),
+        )
+        .with_reference(String::from("REF-1"));
+        transaction.mark_reconciled();
+
+        let serialized = serde_yaml::to_string(&TransactionsWrapper {
+            transactions: vec![transaction],
+        })?;
+        let deserialized: TransactionsWrapper = serde_yaml::from_str(&serialized)?;
+
+        assert_eq!(serialized, expected_str);
+        assert_eq!(deserialized.transactions[0].reference(), Some("REF-1"));
+        assert!(deserialized.transactions[0].is_reconciled());
+        Ok(())
+    }
 }
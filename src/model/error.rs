@@ -1,25 +1,468 @@
+use crate::model::entity::{AmountEntity, TaxCode};
+use chrono::NaiveDate;
 use std::error::Error;
-use std::fmt::{format, Display, Formatter, Pointer};
+use std::fmt::{Display, Formatter};
 
 pub type Result<T> = std::result::Result<T, AccError>;
 
-#[derive(Debug, Clone)]
-pub struct AccError {
-    error_message: String,
+#[derive(Debug)]
+pub enum AccError {
+    /// The ledger file is not valid yaml or does not match the ledger definition.
+    Parse {
+        message: String,
+        line: Option<usize>,
+        column: Option<usize>,
+    },
+    /// The ledger could not be written as yaml.
+    Serialization(String),
+    /// One or more transactions cannot be booked.
+    InvalidTransactions(Vec<TransactionError>),
+    /// The same account name is defined more than once.
+    DuplicateAccount {
+        name: String,
+        first_group: String,
+        second_group: String,
+    },
+    /// The same account number is used by two accounts.
+    DuplicateAccountNumber {
+        number: u32,
+        first_account: String,
+        second_account: String,
+    },
+    /// Active and passive totals differ.
+    UnbalancedLedger {
+        active: AmountEntity,
+        passive: AmountEntity,
+    },
+    /// A foreign currency has no exchange rate.
+    MissingRate(String),
+    /// Accounts are kept in foreign currencies but no account for exchange differences is configured.
+    MissingFxAccount,
+    /// The configured account for exchange differences is not defined.
+    UnknownFxAccount(String),
+    /// Transactions with tax codes are booked but no input-tax and output-tax accounts are configured.
+    MissingVatAccounts,
+    /// No VAT rate of the tax code is valid at the date.
+    MissingVatRate {
+        code: TaxCode,
+        date: NaiveDate,
+    },
+    /// The account the profit or loss is closed onto is no equity account that can be booked onto.
+    InvalidEquityAccount(String),
+    /// A bank statement could not be read, `line` is the line in the statement file.
+    Import {
+        line: Option<usize>,
+        message: String,
+    },
+    /// An assignment rule of the ledger is invalid, `number` is its position starting at 1.
+    InvalidRule {
+        number: usize,
+        message: String,
+    },
+    /// Only working capital accounts without sub-accounts can be reconciled with a bank statement.
+    InvalidReconciliationAccount(String),
+    /// A referenced account is not defined.
+    UnknownAccount(String),
+    /// The fiscal year definition is inconsistent.
+    InvalidFiscalYear(String),
+    /// The start amounts of the accounts do not form a consistent opening balance.
+    InvalidOpeningBalances(Vec<OpeningProblem>),
+    /// Entries of a ledger-cli/hledger journal cannot be read or represented in the ledger.
+    InvalidJournal(Vec<EntryError>),
+    /// A calculated amount exceeds the range of amounts.
+    AmountOverflow,
+    Io(std::io::Error),
 }
 
 impl Display for AccError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Error: {}", self.error_message)
+        write!(f, "Error: ")?;
+        match self {
+            AccError::Parse {
+                message,
+                line: Some(line),
+                column: Some(column),
+            } => write!(
+                f,
+                "could not parse ledger at line {line} column {column}: {message}"
+            ),
+            AccError::Parse { message, .. } => write!(f, "could not parse ledger: {message}"),
+            AccError::Serialization(message) => {
+                write!(f, "could not serialize ledger: {message}")
+            }
+            AccError::InvalidTransactions(errors) => {
+                write!(f, "{} invalid transaction(s)", errors.len())?;
+                for error in errors.iter() {
+                    write!(f, "\n  {error}")?;
+                }
+                Ok(())
+            }
+            AccError::DuplicateAccount {
+                name,
+                first_group,
+                second_group,
+            } => write!(
+                f,
+                "Account {name} is defined in {first_group} and in {second_group}"
+            ),
+            AccError::DuplicateAccountNumber {
+                number,
+                first_account,
+                second_account,
+            } => write!(
+                f,
+                "Account number {number} is used by {first_account} and by {second_account}"
+            ),
+            AccError::UnbalancedLedger { active, passive } => match active.checked_sub(*passive) {
+                Ok(difference) => write!(
+                    f,
+                    "active total {active} and passive total {passive} differ by {difference}"
+                ),
+                Err(_) => write!(
+                    f,
+                    "active total {active} and passive total {passive} differ"
+                ),
+            },
+            AccError::MissingRate(currency) => {
+                write!(f, "no exchange rate defined for currency {currency}")
+            }
+            AccError::MissingFxAccount => write!(
+                f,
+                "accounts in foreign currencies need an fx-account for exchange differences"
+            ),
+            AccError::UnknownFxAccount(account) => {
+                write!(f, "fx-account {account} not found in defined accounts")
+            }
+            AccError::MissingVatAccounts => write!(
+                f,
+                "taxed transactions need input-tax and output-tax accounts in the vat definition"
+            ),
+            AccError::MissingVatRate { code, date } => {
+                write!(f, "no {code} VAT rate defined at {date}")
+            }
+            AccError::InvalidEquityAccount(account) => write!(
+                f,
+                "Account {account} is no equity account in base currency without sub-accounts"
+            ),
+            AccError::Import {
+                line: Some(line),
+                message,
+            } => write!(f, "could not import statement at line {line}: {message}"),
+            AccError::Import { message, .. } => write!(f, "could not import statement: {message}"),
+            AccError::InvalidRule { number, message } => {
+                write!(f, "invalid rule {number}: {message}")
+            }
+            AccError::InvalidReconciliationAccount(account) => {
+                write!(
+                    f,
+                    "Account {account} is no working capital account without sub-accounts"
+                )
+            }
+            AccError::UnknownAccount(account) => {
+                write!(f, "Account {account} not found in defined accounts")
+            }
+            AccError::InvalidFiscalYear(message) => write!(f, "invalid fiscal year: {message}"),
+            AccError::InvalidOpeningBalances(problems) => {
+                write!(f, "inconsistent opening balances")?;
+                for problem in problems.iter() {
+                    write!(f, "\n  {problem}")?;
+                }
+                Ok(())
+            }
+            AccError::InvalidJournal(errors) => {
+                write!(
+                    f,
+                    "journal cannot be imported, {} problem(s) found",
+                    errors.len()
+                )?;
+                for error in errors.iter() {
+                    write!(f, "\n  {error}")?;
+                }
+                Ok(())
+            }
+            AccError::AmountOverflow => write!(f, "amount exceeds the supported range"),
+            AccError::Io(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TransactionProblem {
+    UnknownAccount(String),
+    ParentAccount(String),
+    SameAccount(String),
+    ZeroAmount,
+    Unbalanced {
+        debit: AmountEntity,
+        credit: AmountEntity,
+    },
+    OutsideFiscalYear(NaiveDate),
+    LockedPeriod {
+        number: u32,
+        date: NaiveDate,
+    },
+    MissingRate {
+        currency: String,
+        date: NaiveDate,
+    },
+    CurrencyMismatch {
+        account: String,
+        currency: String,
+    },
+    AmountOverflow,
+}
+
+impl Display for TransactionProblem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TransactionProblem::UnknownAccount(account) => {
+                write!(f, "Account {account} not found in defined accounts")
+            }
+            TransactionProblem::ParentAccount(account) => {
+                write!(
+                    f,
+                    "Account {account} has sub-accounts and cannot be booked onto"
+                )
+            }
+            TransactionProblem::SameAccount(account) => {
+                write!(f, "Account {account} is debited and credited")
+            }
+            TransactionProblem::ZeroAmount => write!(f, "amount is zero"),
+            TransactionProblem::Unbalanced { debit, credit } => {
+                write!(f, "debit total {debit} differs from credit total {credit}")
+            }
+            TransactionProblem::OutsideFiscalYear(date) => {
+                write!(f, "date {date} is outside of the fiscal year")
+            }
+            TransactionProblem::LockedPeriod { number, date } => {
+                write!(f, "date {date} is in locked period {number}")
+            }
+            TransactionProblem::MissingRate { currency, date } => {
+                write!(f, "no exchange rate for {currency} at {date}")
+            }
+            TransactionProblem::CurrencyMismatch { account, currency } => {
+                write!(f, "Account {account} cannot be booked in {currency}")
+            }
+            TransactionProblem::AmountOverflow => {
+                write!(f, "total exceeds the supported range of amounts")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OpeningProblem {
+    Unbalanced {
+        active: AmountEntity,
+        passive: AmountEntity,
+    },
+    IncomeStart {
+        account: String,
+        start: AmountEntity,
+    },
+}
+
+impl Display for OpeningProblem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OpeningProblem::Unbalanced { active, passive } => {
+                match active.checked_sub(*passive) {
+                    Ok(difference) => write!(
+                        f,
+                        "opening active total {active} and passive total {passive} differ by {difference}"
+                    ),
+                    Err(_) => write!(
+                        f,
+                        "opening active total {active} and passive total {passive} differ"
+                    ),
+                }
+            }
+            OpeningProblem::IncomeStart { account, start } => write!(
+                f,
+                "income account {account} starts at {start} instead of zero"
+            ),
+        }
+    }
+}
+
+/// Problem of a single transaction, `index` is the zero based position in the ledger file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransactionError {
+    pub index: usize,
+    pub description: String,
+    pub problem: TransactionProblem,
+}
+
+impl Display for TransactionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Transaction {} ({}): {}",
+            self.index + 1,
+            self.description,
+            self.problem
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EntryProblem {
+    InvalidSyntax(String),
+    Unsupported(String),
+    UnknownPrefix(String),
+    ParentAccount(String),
+    DuplicateAccount {
+        account: String,
+        other: String,
+    },
+    VirtualPosting(String),
+    Cost(String),
+    MissingAmounts,
+    TooFewPostings,
+    MixedCurrencies(Vec<String>),
+    Unbalanced {
+        difference: AmountEntity,
+        currency: String,
+    },
+    CurrencyMismatch {
+        account: String,
+        currency: String,
+    },
+    AmountOverflow,
+}
+
+impl Display for EntryProblem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EntryProblem::InvalidSyntax(message) => write!(f, "{message}"),
+            EntryProblem::Unsupported(what) => write!(f, "{what} are not supported"),
+            EntryProblem::UnknownPrefix(account) => {
+                write!(f, "Account {account} is not below any account prefix")
+            }
+            EntryProblem::ParentAccount(account) => {
+                write!(
+                    f,
+                    "Account {account} has sub-accounts and cannot be booked onto"
+                )
+            }
+            EntryProblem::DuplicateAccount { account, other } => write!(
+                f,
+                "Account {account} has the same name below its prefix as {other}"
+            ),
+            EntryProblem::VirtualPosting(account) => {
+                write!(f, "virtual posting onto {account} has no counterpart")
+            }
+            EntryProblem::Cost(account) => write!(
+                f,
+                "posting onto {account} converts with a cost, use exchange rates instead"
+            ),
+            EntryProblem::MissingAmounts => write!(f, "more than one posting without amount"),
+            EntryProblem::TooFewPostings => write!(f, "less than two postings with an amount"),
+            EntryProblem::MixedCurrencies(currencies) => write!(
+                f,
+                "postings in {} would need an implicit conversion",
+                currencies.join(" and ")
+            ),
+            EntryProblem::Unbalanced {
+                difference,
+                currency,
+            } => write!(f, "postings do not balance by {difference} {currency}"),
+            EntryProblem::CurrencyMismatch { account, currency } => write!(
+                f,
+                "opening balance of {account} in {currency} needs an account kept in {currency}"
+            ),
+            EntryProblem::AmountOverflow => write!(f, "amounts exceed the supported range"),
+        }
+    }
+}
+
+/// Problem of a journal entry or directive, `line` is its first line in the journal file starting at 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntryError {
+    pub line: usize,
+    pub description: String,
+    pub problem: EntryProblem,
+}
+
+impl Display for EntryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Line {} ({}): {}",
+            self.line, self.description, self.problem
+        )
+    }
+}
+
+impl Error for AccError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AccError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<serde_yaml::Error> for AccError {
+    fn from(err: serde_yaml::Error) -> Self {
+        let location = err.location();
+        let message = err.to_string();
+        // The message ends with the location, which is kept separately.
+        let message = match &location {
+            Some(location) => message
+                .strip_suffix(&format!(
+                    " at line {} column {}",
+                    location.line(),
+                    location.column()
+                ))
+                .unwrap_or(&message)
+                .to_string(),
+            None => message,
+        };
+        AccError::Parse {
+            message,
+            line: location.as_ref().map(|location| location.line()),
+            column: location.as_ref().map(|location| location.column()),
+        }
+    }
+}
+
+impl From<csv::Error> for AccError {
+    fn from(err: csv::Error) -> Self {
+        AccError::Import {
+            line: err.position().map(|position| position.line() as usize),
+            message: err.to_string(),
+        }
+    }
+}
+
+impl From<std::io::Error> for AccError {
+    fn from(err: std::io::Error) -> Self {
+        AccError::Io(err)
     }
 }
 
-impl Error for AccError {}
+#[cfg(test)]
+mod tests {
+    use super::AccError;
 
-impl AccError {
-    pub fn new(message: String) -> Self {
-        AccError {
-            error_message: message,
+    #[test]
+    fn test_parse_error_with_location() {
+        let err: serde_yaml::Error =
+            serde_yaml::from_str::<Vec<u32>>("- 1\n- a").expect_err("invalid yaml");
+        let error = AccError::from(err);
+        match &error {
+            AccError::Parse {
+                message,
+                line,
+                column,
+            } => {
+                assert_eq!(*line, Some(2));
+                assert_eq!(*column, Some(3));
+                assert!(!message.contains("line"));
+            }
+            _ => panic!("expected parse error"),
         }
+        assert_eq!(error.to_string().matches("line 2").count(), 1);
     }
 }